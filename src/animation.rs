@@ -161,6 +161,7 @@ impl AnimationTick {
         self.tick + self.range.start == self.range.end
     }
 
+    #[expect(dead_code, reason = "lib in progress")]
     pub fn tick(&self) -> usize {
         self.tick + self.range.start
     }
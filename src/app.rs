@@ -1,3 +1,5 @@
+use std::io;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -6,24 +8,100 @@ use ratatui::Frame;
 use ratatui::layout::Rect;
 
 use crate::animation::AnimationTicker;
-use crate::job::Job;
+use crate::config::{self, Config};
+use crate::job::graph::{self, DependencyError};
+use crate::job::{self, Job};
 use crate::theme::AppTheme;
+use crate::ui::common;
 use crate::ui::popup::{self, SharedPopupState};
 use crate::ui::{Action, Component};
+use crate::{clipboard, procfile, session};
 
-type Popups = (popup::EditPopup, popup::NewJobPopup, popup::RenamePopup);
+type Popups = (
+    popup::DetailsPopup,
+    popup::DiscoverPopup,
+    popup::EditPopup,
+    popup::ExportPopup,
+    popup::NewJobPopup,
+    popup::RenamePopup,
+);
 pub type PopupsState = SharedPopupState<Popups>;
 
+/// Where key events are routed to
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputMode {
+    /// Keys are handled by buj bindings
+    #[default]
+    Normal,
+    /// Keys are forwarded to the current job
+    Passthrough,
+    /// Prefix key was pressed on passthrough, the next key is handled by buj
+    Prefix,
+    /// Typing on the search prompt of the current job
+    Search,
+    /// Selecting output of the current job to copy it
+    Copy,
+}
+
+/// Short message shown on the footer until the next key press
+pub struct Notice {
+    pub text: String,
+    pub error: bool,
+}
+
+impl Notice {
+    pub fn info(text: impl ToString) -> Self {
+        Self {
+            text: text.to_string(),
+            error: false,
+        }
+    }
+
+    pub fn error(text: impl ToString) -> Self {
+        Self {
+            text: text.to_string(),
+            error: true,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct App {
     pub current_job: Option<usize>,
+    pub mode: InputMode,
+    /// Query of the search prompt
+    pub search: common::InputState,
+    /// Search the query as a regex instead of plain text
+    pub search_regex: bool,
+    pub copy: crate::ui::job::copy::CopyMode,
+    pub panes: crate::ui::job::panes::Panes,
+    /// The "All jobs" view
+    pub log: crate::ui::job::log::LogView,
+    /// Where the terminal of the current job was last drawn
+    pub vterm_area: Rect,
+    /// Command also receiving copied text, `None` only uses OSC 52
+    pub clipboard_command: Option<clipboard::Fallback>,
+    pub notice: Option<Notice>,
     pub jobs: Vec<Job>,
+    /// Jobs removed while running, kept until they exit
+    pub stopping: Vec<Job>,
+    /// Shell mode of the jobs created from the New Job popup
+    pub default_shell: Option<job::shell::Shell>,
+    /// Project file the jobs were loaded from
+    pub config_path: Option<PathBuf>,
+    /// The project file was just written by an export, its reload has
+    /// nothing to report
+    pub config_exported: bool,
+    /// Jobs of the project file or Procfile to load
+    pub job_filter: config::JobFilter,
     pub theme: Arc<AppTheme>,
     pub anim: AnimationTicker,
     pub sidebar_anim: AnimationTicker,
     pub popup: PopupsState,
 
+    pub popup_discover: popup::DiscoverPopup,
     pub popup_edit: popup::EditPopup,
+    pub popup_export: popup::ExportPopup,
     pub popup_new_job: popup::NewJobPopup,
     pub popup_rename: popup::RenamePopup,
 }
@@ -46,6 +124,12 @@ impl App {
         }
     }
 
+    /// Go straight to the end of the intro animation
+    pub fn skip_intro(&mut self) {
+        self.anim.tick = self.anim.len;
+        self.anim.end();
+    }
+
     // Start sidebar animation on start with conditions
     pub fn update_sidebar(&mut self) {
         if self.sidebar_anim.running() {
@@ -78,30 +162,590 @@ impl App {
         self.current_job = Some(idx);
     }
 
-    /// Returns whenever needs to waits
+    /// Remove a job, keeping the current job and panes on the same jobs
+    pub fn remove_job(&mut self, idx: usize) -> Job {
+        let job = self.jobs.remove(idx);
+
+        self.current_job = match self.current_job {
+            Some(current) if current > idx => Some(current - 1),
+            Some(current) if current == idx => idx.min(self.jobs.len().saturating_sub(1)).into(),
+            current => current,
+        }
+        .filter(|&current| current < self.jobs.len());
+
+        self.panes.remove_job(idx);
+
+        job
+    }
+
+    /// Waits for new output of any job shown on a pane, returns whenever
+    /// needs to wait
     pub async fn job_tick(&self) -> bool {
-        if let Some(job) = self.current_job() {
-            job.notify.notified().await;
+        if self.log.open {
+            job::log::LOG.notify.notified().await;
+            return true;
+        }
+
+        let notified: Vec<_> = self
+            .panes
+            .visible_jobs(self.current_job)
+            .filter_map(|idx| self.jobs.get(idx))
+            .map(|job| Box::pin(job.notify.notified()))
+            .collect();
+
+        if notified.is_empty() {
+            return false;
+        }
 
-            true
+        futures::future::select_all(notified).await;
+
+        true
+    }
+
+    /// Start all jobs that are not running, each one after its dependencies are ready
+    pub fn start_all(&mut self) -> Result<(), DependencyError> {
+        self.start_with_deps(0..self.jobs.len())
+    }
+
+    /// Start `jobs` and the jobs they depend on, each one after its
+    /// dependencies are ready
+    pub fn start_with_deps(
+        &mut self,
+        jobs: impl IntoIterator<Item = usize>,
+    ) -> Result<(), DependencyError> {
+        let mut pending: Vec<usize> = jobs.into_iter().collect();
+        let mut marked = vec![false; self.jobs.len()];
+
+        while let Some(idx) = pending.pop() {
+            if std::mem::replace(&mut marked[idx], true) {
+                continue;
+            }
+
+            pending.extend(graph::dependencies(&self.jobs, idx)?);
+        }
+
+        for idx in graph::start_order(&self.jobs)? {
+            let job = &mut self.jobs[idx];
+
+            if marked[idx] && !job.is_alive() {
+                job.waiting_deps = true;
+            }
+        }
+
+        self.update_jobs();
+
+        Ok(())
+    }
+
+    /// Take the jobs and settings of a project file, starting the jobs
+    /// marked as autostart
+    pub fn load_config(&mut self, config: Config) {
+        self.default_shell = config.default_shell;
+        self.clipboard_command = config.clipboard;
+        self.config_path = Some(config.path);
+
+        let unknown = self.job_filter.unknown(&config.jobs);
+
+        if let Some(name) = unknown.first() {
+            self.notice = Some(Notice::error(format!(
+                "No job of {} is named or tagged `{name}`",
+                config::FILE_NAME
+            )));
+            return;
+        }
+
+        let (skipped, jobs): (Vec<Job>, Vec<Job>) = self
+            .job_filter
+            .apply(config.jobs)
+            .into_iter()
+            .partition(|new| self.jobs.iter().any(|job| job.title == new.title));
+
+        let skipped: Vec<String> = skipped.into_iter().map(|job| job.title).collect();
+
+        if jobs.is_empty() {
+            if !skipped.is_empty() {
+                self.notice = Some(Notice::error(title_conflict(&skipped)));
+            }
+            return;
+        }
+
+        let first = self.jobs.len();
+        let loaded = jobs.len();
+
+        for job in jobs {
+            self.push_job(job);
+        }
+
+        self.current_job = Some(first);
+
+        let autostart: Vec<usize> = (first..self.jobs.len())
+            .filter(|&idx| self.jobs[idx].autostart)
+            .collect();
+
+        self.notice = Some(match self.start_with_deps(autostart) {
+            Ok(()) if !skipped.is_empty() => Notice::error(title_conflict(&skipped)),
+            Ok(()) => Notice::info(format!(
+                "Loaded {loaded} job{} from {}",
+                if loaded == 1 { "" } else { "s" },
+                config::FILE_NAME
+            )),
+            Err(err) => Notice::error(err),
+        });
+    }
+
+    /// Load a project file and watch it, applying its changes while running
+    pub fn open_config(&mut self, path: PathBuf) {
+        match config::load(&path) {
+            Ok(config) => self.load_config(config),
+            Err(err) => {
+                // Fixing the file loads it
+                self.config_path = Some(path.clone());
+                self.notice = Some(Notice::error(err));
+            }
+        }
+
+        if let Err(err) = config::watch::spawn(&path) {
+            self.notice = Some(Notice::error(format!(
+                "Can't watch {}: {err}",
+                config::FILE_NAME
+            )));
+        }
+    }
+
+    /// Apply the changes of the project file: add new jobs, remove deleted
+    /// ones and restart the ones spawned differently
+    pub fn reload_config(&mut self) {
+        let Some(path) = self.config_path.clone() else {
+            return;
+        };
+
+        let mut config = match config::load(&path) {
+            Ok(config) => config,
+            Err(err) => {
+                self.notice = Some(Notice::error(err));
+                return;
+            }
+        };
+
+        config.jobs = self.job_filter.apply(config.jobs);
+
+        self.default_shell = config.default_shell;
+        self.clipboard_command = config.clipboard;
+
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        let mut restarted = Vec::new();
+        let mut updated = Vec::new();
+        let mut skipped = Vec::new();
+        let mut error = None;
+
+        for idx in (0..self.jobs.len()).rev() {
+            let job = &self.jobs[idx];
+
+            if job.from_config && !config.jobs.iter().any(|new| new.title == job.title) {
+                let mut job = self.remove_job(idx);
+                removed.push(job.title.clone());
+
+                if job.stop() {
+                    self.stopping.push(job);
+                }
+            }
+        }
+        removed.reverse();
+
+        let current_job = self.current_job;
+        let first_added = self.jobs.len();
+
+        for new in config.jobs {
+            let Some(idx) = self
+                .jobs
+                .iter()
+                .position(|job| job.from_config && job.title == new.title)
+            else {
+                // Titles identify the jobs of the file, like on load
+                if self.jobs.iter().any(|job| job.title == new.title) {
+                    skipped.push(new.title);
+                } else {
+                    added.push(new.title.clone());
+                    self.push_job(new);
+                }
+                continue;
+            };
+
+            let job = &mut self.jobs[idx];
+
+            match config::update_job(job, new) {
+                config::JobChange::Unchanged => {}
+                config::JobChange::Respawn if job.is_alive() => {
+                    if let Err(err) = job.restart() {
+                        error = Some(err.to_string());
+                    }
+                    restarted.push(job.title.clone());
+                }
+                config::JobChange::Respawn | config::JobChange::Updated => {
+                    updated.push(job.title.clone());
+                }
+            }
+        }
+
+        // Added jobs don't take the focus, unless there was nothing to show
+        self.current_job = current_job.or(self.current_job);
+
+        let autostart: Vec<usize> = (first_added..self.jobs.len())
+            .filter(|&idx| self.jobs[idx].autostart)
+            .collect();
+
+        if let Err(err) = self.start_with_deps(autostart) {
+            error = Some(err.to_string());
+        }
+
+        if !skipped.is_empty() {
+            error = Some(title_conflict(&skipped));
+        }
+
+        if let Some(error) = error {
+            self.notice = Some(Notice::error(error));
+            return;
+        }
+
+        let exported = std::mem::take(&mut self.config_exported);
+
+        let summary: Vec<String> = [
+            ("added", added),
+            ("removed", removed),
+            ("restarted", restarted),
+            ("updated", updated),
+        ]
+        .into_iter()
+        .filter(|(_, titles)| !titles.is_empty())
+        .map(|(what, titles)| format!("{what} {}", titles.join(", ")))
+        .collect();
+
+        if exported && summary.is_empty() {
+            return;
+        }
+
+        self.notice = Some(Notice::info(if summary.is_empty() {
+            format!("{} reloaded, nothing changed", config::FILE_NAME)
         } else {
-            false
+            format!("{} reloaded: {}", config::FILE_NAME, summary.join("; "))
+        }));
+    }
+
+    /// Add a job per process of a Procfile and start them all, like
+    /// `foreman start`
+    pub fn load_procfile(&mut self, path: &Path) {
+        let jobs = match procfile::load(path) {
+            Ok(jobs) => jobs,
+            Err(err) => {
+                self.notice = Some(Notice::error(err));
+                return;
+            }
+        };
+
+        if let Some(name) = self.job_filter.unknown(&jobs).first() {
+            self.notice = Some(Notice::error(format!(
+                "No process of {} is named `{name}`",
+                path.display()
+            )));
+            return;
+        }
+
+        let jobs = self.job_filter.apply(jobs);
+
+        let first = self.jobs.len();
+        let loaded = jobs.len();
+
+        for job in jobs {
+            self.push_job(job);
+        }
+
+        self.current_job = Some(first);
+
+        self.notice = Some(match self.start_with_deps(first..self.jobs.len()) {
+            Ok(()) => Notice::info(format!(
+                "Loaded {loaded} process{} from {}",
+                if loaded == 1 { "" } else { "es" },
+                path.display()
+            )),
+            Err(err) => Notice::error(err),
+        });
+    }
+
+    /// Add and start a job per command given on the command line, a shell
+    /// runs the ones given as a single argument
+    pub fn preload(&mut self, commands: Vec<(String, bool)>) {
+        let first = self.jobs.len();
+
+        for (cmd, shell) in commands {
+            let mut job = Job::new(&cmd);
+            job.shell = self
+                .default_shell
+                .clone()
+                .or_else(|| shell.then(job::shell::Shell::default));
+
+            if let Err(err) = job.start() {
+                self.notice = Some(Notice::error(err));
+            }
+
+            self.push_job(job);
+        }
+
+        if first < self.jobs.len() {
+            self.current_job = Some(first);
+        }
+    }
+
+    /// Add the jobs saved when quitting from this directory, starting the ones
+    /// that were running and the ones with the `always` restart policy
+    pub fn recover_session(&mut self) {
+        let saved = match session::load() {
+            Ok(Some(saved)) => saved,
+            Ok(None) => {
+                self.notice = Some(Notice::info("No saved jobs for this directory"));
+                return;
+            }
+            Err(err) => {
+                self.notice = Some(Notice::error(err));
+                return;
+            }
+        };
+
+        let first = self.jobs.len();
+        let mut running = Vec::new();
+
+        for saved in saved {
+            if saved.should_start() {
+                running.push(self.jobs.len());
+            }
+
+            self.push_job(saved.into_job());
         }
+
+        let recovered = self.jobs.len() - first;
+        self.current_job = Some(first);
+
+        self.notice = Some(match self.start_with_deps(running) {
+            Ok(()) => Notice::info(format!(
+                "Recovered {recovered} job{}",
+                if recovered == 1 { "" } else { "s" }
+            )),
+            Err(err) => Notice::error(err),
+        });
     }
 
-    pub fn kill_jobs(&mut self) {
+    /// Write the jobs to the project file, or to a new one in the current
+    /// directory. Overwriting a file asks first, showing the changes.
+    pub fn export_config(&mut self) {
+        // The jobs left out would be removed from the file
+        if !self.job_filter.is_empty() {
+            self.notice = Some(Notice::error(
+                "Cannot export while jobs are selected with --only or --except",
+            ));
+            return;
+        }
+
+        let path = match self.config_path.clone() {
+            Some(path) => path,
+            None => match std::env::current_dir() {
+                Ok(dir) => dir.join(config::FILE_NAME),
+                Err(err) => {
+                    self.notice = Some(Notice::error(err));
+                    return;
+                }
+            },
+        };
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+
+        let new = match config::export::render(
+            &self.jobs,
+            dir,
+            self.default_shell.as_ref(),
+            self.clipboard_command.as_ref(),
+        ) {
+            Ok(new) => new,
+            Err(err) => {
+                self.notice = Some(Notice::error(err));
+                return;
+            }
+        };
+
+        match std::fs::read_to_string(&path) {
+            Ok(old) if old == new => {
+                self.notice = Some(Notice::info(format!("{} is up to date", config::FILE_NAME)));
+            }
+            Ok(old) => {
+                self.popup_export.path = path;
+                self.popup_export.old = old;
+                self.popup_export.new = new;
+                PopupsState::open::<popup::ExportPopup>(self);
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => self.write_export(path, &new),
+            Err(err) => self.notice = Some(Notice::error(err)),
+        }
+    }
+
+    /// Write an exported project file, the jobs become the ones it declares
+    pub fn write_export(&mut self, path: PathBuf, text: &str) {
+        if let Err(err) = std::fs::write(&path, text) {
+            self.notice = Some(Notice::error(format!(
+                "Cannot write {}: {err}",
+                config::FILE_NAME
+            )));
+            return;
+        }
+
+        // Take the definitions as loading the file makes them, so reloading
+        // it doesn't see changes
+        match config::load(&path) {
+            Ok(config) => {
+                for new in config.jobs {
+                    if let Some(job) = self.jobs.iter_mut().find(|job| job.title == new.title) {
+                        config::update_job(job, new);
+                        job.from_config = true;
+                    }
+                }
+            }
+            Err(err) => {
+                self.notice = Some(Notice::error(err));
+                return;
+            }
+        }
+
+        if self.config_path.is_none() {
+            if let Err(err) = config::watch::spawn(&path) {
+                self.notice = Some(Notice::error(format!(
+                    "Can't watch {}: {err}",
+                    config::FILE_NAME
+                )));
+                return;
+            }
+
+            self.config_path = Some(path);
+        }
+
+        self.config_exported = true;
+
+        let exported = self.jobs.len();
+        self.notice = Some(Notice::info(format!(
+            "Exported {exported} job{} to {}",
+            if exported == 1 { "" } else { "s" },
+            config::FILE_NAME
+        )));
+    }
+
+    /// Returns whenever a running job needs its uptime redrawn
+    pub async fn uptime_tick(&self) -> bool {
+        if !self.jobs.iter().any(Job::is_alive) {
+            return false;
+        }
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        true
+    }
+
+    pub fn update_jobs(&mut self) {
+        let order =
+            graph::start_order(&self.jobs).unwrap_or_else(|_| (0..self.jobs.len()).collect());
+
+        for idx in order {
+            let Ok(deps) = graph::dependencies(&self.jobs, idx) else {
+                continue;
+            };
+
+            let deps_up = deps.iter().all(|dep| self.jobs[*dep].is_ready());
+            let deps_exited = deps.iter().any(|dep| {
+                let dep = &self.jobs[*dep];
+                dep.running.is_some() && !dep.is_alive()
+            });
+
+            let job = &mut self.jobs[idx];
+
+            if job.waiting_deps && deps_up {
+                job.waiting_deps = false;
+
+                if let Err(err) = job.start() {
+                    self.notice = Some(Notice::error(format!("{}: {err}", job.title)));
+                }
+            } else if job.stop_with_deps && deps_exited && job.is_alive() && !job.is_stopping() {
+                job.stop();
+                job.waiting_deps = true;
+            }
+        }
+
         for job in &mut self.jobs {
+            if let Err(err) = job.update() {
+                self.notice = Some(Notice::error(format!("{}: {err}", job.title)));
+            }
+        }
+
+        self.stopping.retain(Job::is_alive);
+    }
+
+    /// Gracefully stop all jobs and wait until they exit
+    pub async fn kill_jobs(&mut self) {
+        let mut deadline = tokio::time::Instant::now();
+
+        for job in &mut self.jobs {
+            job.pending_restart = false;
+
+            if job.stop() {
+                deadline = deadline.max(tokio::time::Instant::now() + job.stop_timeout);
+            }
+        }
+
+        // Removed jobs already got their stop signal
+        for job in &self.stopping {
+            if let Some(since) = job.running.as_ref().and_then(|job| job.stopping) {
+                deadline = deadline.max(since + job.stop_timeout);
+            }
+        }
+
+        // Leave some room for the escalated kill to be reaped
+        let deadline = deadline + Duration::from_millis(500);
+
+        while self.jobs.iter().chain(&self.stopping).any(Job::is_alive) {
+            if tokio::time::timeout_at(deadline, job::LIFECYCLE.notified())
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+
+        // Leftovers of jobs whose leader already exited
+        for job in self.jobs.iter_mut().chain(&mut self.stopping) {
             job.kill();
         }
     }
 }
 
+/// Error for jobs of the project file not added because their title is taken
+fn title_conflict(titles: &[String]) -> String {
+    format!(
+        "Not loaded from {}, title already taken: {}",
+        config::FILE_NAME,
+        titles.join(", ")
+    )
+}
+
 impl Component for App {
     type State = Self;
 
     async fn handle_event(state: &mut Self::State, event: Event) -> Action {
         PopupsState::handle_event(state, event.clone()).await?;
 
+        match state.mode {
+            InputMode::Normal => {}
+            InputMode::Search => crate::ui::job::handle_search(state, event.clone())?,
+            InputMode::Copy => crate::ui::job::copy::handle_event(state, event.clone()).await?,
+            InputMode::Passthrough | InputMode::Prefix => {
+                crate::ui::job::handle_passthrough(state, event.clone()).await?;
+            }
+        }
+
         match event {
             Event::Key(key_event) => Self::handle_key_events(state, key_event).await?,
             Event::Mouse(mouse_event) => Self::handle_mouse_events(state, mouse_event).await?,
@@ -113,9 +757,26 @@ impl Component for App {
     }
 
     async fn handle_key_events(state: &mut Self::State, key: KeyEvent) -> Action {
+        state.notice = None;
+
         match key.code {
             KeyCode::Char('q') => Action::Quit,
-            KeyCode::Char('n') => {
+            KeyCode::Char('D') => {
+                PopupsState::open::<popup::DiscoverPopup>(state);
+                Action::Tick
+            }
+            KeyCode::Char('E') => {
+                state.export_config();
+                Action::Tick
+            }
+            KeyCode::Char('s') => {
+                if let Err(err) = state.start_all() {
+                    state.notice = Some(Notice::error(err));
+                }
+                Action::Tick
+            }
+            // Next match while searching
+            KeyCode::Char('n') if crate::ui::job::search_info(state).is_none() => {
                 PopupsState::open::<popup::NewJobPopup>(state);
                 Action::Tick
             }
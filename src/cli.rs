@@ -0,0 +1,281 @@
+//! Command line arguments.
+
+use std::path::PathBuf;
+
+use ratatui::style::Color;
+use thiserror::Error;
+
+use crate::job::shell;
+
+pub const USAGE: &str = "\
+Usage: buj [OPTIONS] [COMMAND [-- COMMAND]...]
+
+Runs each COMMAND as a job, along with the jobs of the job file.";
+
+pub const HELP: &str = "\
+Usage: buj [OPTIONS] [COMMAND [-- COMMAND]...]
+
+Runs each COMMAND as a job, along with the jobs of the job file. A COMMAND
+given as a single argument is a shell command line, several words are run
+directly:
+
+    buj 'npm run dev | tee log' -- cargo watch -x run
+
+Options must come before the first COMMAND.
+
+Options:
+  -f, --file <PATH>        Job file to load instead of looking up buj.toml
+                           from the current directory upwards
+  -P, --procfile <PATH>    Load the processes of a Procfile instead, with
+                           the .env file next to it
+      --cwd <DIR>          Run from DIR
+      --only <NAMES>       Only load the jobs with these titles or tags,
+                           comma separated, can be repeated
+      --except <NAMES>     Don't load the jobs with these titles or tags.
+                           Dependencies of the loaded jobs are always loaded
+      --theme <COLOR>      Accent color: a name (magenta, lightblue...),
+                           #rrggbb or a 256 colors index
+      --no-intro           Skip the intro animation
+  -h, --help               Print this help
+  -V, --version            Print the version
+
+Exit status:
+  0  buj quit normally
+  1  buj couldn't start: bad --cwd, missing --file, terminal error
+  2  usage error: unknown option, missing value...";
+
+#[derive(Debug, Error)]
+pub enum UsageError {
+    #[error("unknown option `{0}`")]
+    UnknownOption(String),
+    #[error("`{0}` needs a value")]
+    MissingValue(String),
+    #[error("`{0}` doesn't take a value")]
+    UnexpectedValue(String),
+    #[error("invalid color `{0}` for `--theme`")]
+    InvalidColor(String),
+    #[error("`--file` and `--procfile` can't be used together")]
+    FileAndProcfile,
+    #[error("empty command")]
+    EmptyCommand,
+}
+
+/// What a command line asks for
+#[derive(Default)]
+pub struct Args {
+    /// Command lines of the jobs to add, with whether it runs through a shell
+    pub commands: Vec<(String, bool)>,
+    pub file: Option<PathBuf>,
+    pub procfile: Option<PathBuf>,
+    pub cwd: Option<PathBuf>,
+    pub only: Vec<String>,
+    pub except: Vec<String>,
+    pub theme: Option<Color>,
+    pub no_intro: bool,
+    pub help: bool,
+    pub version: bool,
+}
+
+/// Parse the arguments, without the program name
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, UsageError> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    let mut commands = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            commands.extend(args);
+            break;
+        }
+
+        if !arg.starts_with('-') || arg == "-" {
+            commands.push(arg);
+            commands.extend(args);
+            break;
+        }
+
+        // `--name=value` and `--name value`
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_owned())),
+            _ => (arg.as_str(), None),
+        };
+
+        let mut value = || {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| UsageError::MissingValue(name.to_owned()))
+        };
+
+        match name {
+            "-f" | "--file" => parsed.file = Some(value()?.into()),
+            "-P" | "--procfile" => parsed.procfile = Some(value()?.into()),
+            "--cwd" => parsed.cwd = Some(value()?.into()),
+            "--only" => parsed.only.extend(names(&value()?)),
+            "--except" => parsed.except.extend(names(&value()?)),
+            "--theme" => {
+                let color = value()?;
+                parsed.theme = Some(color.parse().map_err(|_| UsageError::InvalidColor(color))?);
+            }
+            "--no-intro" | "-h" | "--help" | "-V" | "--version" if inline.is_some() => {
+                return Err(UsageError::UnexpectedValue(name.to_owned()));
+            }
+            "--no-intro" => parsed.no_intro = true,
+            "-h" | "--help" => parsed.help = true,
+            "-V" | "--version" => parsed.version = true,
+            _ => return Err(UsageError::UnknownOption(name.to_owned())),
+        }
+    }
+
+    for command in commands.split(|arg| arg == "--") {
+        if !command.is_empty() {
+            parsed.commands.push(command_line(command)?);
+        }
+    }
+
+    if parsed.file.is_some() && parsed.procfile.is_some() {
+        return Err(UsageError::FileAndProcfile);
+    }
+
+    Ok(parsed)
+}
+
+/// A single word is a shell command line, several words are quoted back
+/// into one to run directly
+fn command_line(words: &[String]) -> Result<(String, bool), UsageError> {
+    if let [line] = words {
+        if line.trim().is_empty() {
+            return Err(UsageError::EmptyCommand);
+        }
+
+        return Ok((line.clone(), true));
+    }
+
+    let line = words
+        .iter()
+        .map(|word| shell::quote(word))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok((line, false))
+}
+
+/// Comma separated names
+fn names(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Args, UsageError> {
+        parse(args.iter().map(|&arg| arg.to_owned()))
+    }
+
+    fn commands(args: &[&str]) -> Vec<(String, bool)> {
+        parse_args(args).unwrap().commands
+    }
+
+    #[test]
+    fn single_word_runs_through_a_shell() {
+        assert_eq!(
+            commands(&["npm run dev | tee log"]),
+            [("npm run dev | tee log".to_owned(), true)]
+        );
+    }
+
+    #[test]
+    fn words_are_quoted_back() {
+        assert_eq!(
+            commands(&["echo", "two words", "it's", ""]),
+            [(r"echo 'two words' 'it'\''s' ''".to_owned(), false)]
+        );
+    }
+
+    #[test]
+    fn commands_split_on_double_dash() {
+        assert_eq!(
+            commands(&["--", "a", "--", "--", "b", "c", "--"]),
+            [("a".to_owned(), true), ("b c".to_owned(), false)]
+        );
+    }
+
+    #[test]
+    fn options_stop_at_the_first_command() {
+        let args = parse_args(&["--no-intro", "ls", "--help"]).unwrap();
+
+        assert!(args.no_intro);
+        assert!(!args.help);
+        assert_eq!(args.commands, [("ls --help".to_owned(), false)]);
+    }
+
+    #[test]
+    fn option_values() {
+        let args = parse_args(&[
+            "-f",
+            "jobs.toml",
+            "--cwd=/tmp",
+            "--only",
+            "web, db,",
+            "--only=worker",
+            "--except",
+            "slow",
+            "--theme=#ff8800",
+        ])
+        .unwrap();
+
+        assert_eq!(args.file, Some(PathBuf::from("jobs.toml")));
+        assert_eq!(args.cwd, Some(PathBuf::from("/tmp")));
+        assert_eq!(args.only, ["web", "db", "worker"]);
+        assert_eq!(args.except, ["slow"]);
+        assert_eq!(args.theme, Some(Color::Rgb(0xff, 0x88, 0x00)));
+        assert!(args.commands.is_empty());
+    }
+
+    #[test]
+    fn inline_value_can_contain_equals() {
+        let args = parse_args(&["--cwd=a=b"]).unwrap();
+
+        assert_eq!(args.cwd, Some(PathBuf::from("a=b")));
+    }
+
+    #[test]
+    fn usage_errors() {
+        assert!(matches!(
+            parse_args(&["--nope"]),
+            Err(UsageError::UnknownOption(name)) if name == "--nope"
+        ));
+        assert!(matches!(
+            parse_args(&["-x=1"]),
+            Err(UsageError::UnknownOption(name)) if name == "-x=1"
+        ));
+        assert!(matches!(
+            parse_args(&["--cwd"]),
+            Err(UsageError::MissingValue(name)) if name == "--cwd"
+        ));
+        assert!(matches!(
+            parse_args(&["--no-intro=1"]),
+            Err(UsageError::UnexpectedValue(name)) if name == "--no-intro"
+        ));
+        assert!(matches!(
+            parse_args(&["--theme", "nocolor"]),
+            Err(UsageError::InvalidColor(color)) if color == "nocolor"
+        ));
+        assert!(matches!(
+            parse_args(&["-f", "a", "-P", "b"]),
+            Err(UsageError::FileAndProcfile)
+        ));
+        assert!(matches!(parse_args(&["  "]), Err(UsageError::EmptyCommand)));
+    }
+
+    #[test]
+    fn lone_dash_is_a_command() {
+        assert_eq!(commands(&["-"]), [("-".to_owned(), true)]);
+    }
+}
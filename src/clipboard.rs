@@ -0,0 +1,91 @@
+//! Copying text to the system clipboard.
+//!
+//! The text is sent to the host terminal with an OSC 52 escape, which works
+//! over ssh but not on every terminal, so a clipboard command (`wl-copy`,
+//! `xclip`...) can also be configured as a fallback.
+
+use std::io::{self, Write};
+use std::process::Stdio;
+
+use crossterm::clipboard::CopyToClipboard;
+use tokio::process::Command;
+
+/// Tried in order when the fallback is [`Fallback::Detect`]
+const FALLBACK_COMMANDS: [(&str, &[&str]); 4] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+];
+
+/// Command also receiving the copied text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fallback {
+    /// The first of [`FALLBACK_COMMANDS`] found in `PATH`, if any
+    Detect,
+    Command(Vec<String>),
+}
+
+/// Copy `text` to the clipboard.
+///
+/// The `fallback` command is run with the text on its stdin, `None` only
+/// relies on the terminal.
+pub fn copy(text: &str, fallback: Option<&Fallback>) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    crossterm::execute!(stdout, CopyToClipboard::to_clipboard_from(text))?;
+    stdout.flush()?;
+
+    let command = match fallback {
+        Some(Fallback::Command(command)) => match command.as_slice() {
+            [program, args @ ..] => Some((program.clone(), args.to_vec())),
+            [] => None,
+        },
+        Some(Fallback::Detect) => detect(),
+        None => None,
+    };
+
+    if let Some((program, args)) = command {
+        run(&program, &args, text.to_owned())?;
+    }
+
+    Ok(())
+}
+
+fn detect() -> Option<(String, Vec<String>)> {
+    let path = std::env::var_os("PATH")?;
+
+    FALLBACK_COMMANDS
+        .iter()
+        .find(|(program, _)| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+        .map(|(program, args)| {
+            (
+                (*program).to_owned(),
+                args.iter().map(|&arg| arg.to_owned()).collect(),
+            )
+        })
+}
+
+fn run(program: &str, args: &[String], text: String) -> io::Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut stdin = child.stdin.take();
+
+    tokio::spawn(async move {
+        use tokio::io::AsyncWriteExt;
+
+        if let Some(ref mut stdin) = stdin {
+            _ = stdin.write_all(text.as_bytes()).await;
+        }
+
+        // Closes stdin, some commands wait for EOF
+        drop(stdin);
+        _ = child.wait().await;
+    });
+
+    Ok(())
+}
@@ -0,0 +1,894 @@
+//! Project file declaring the jobs of a project (`buj.toml`).
+//!
+//! ```toml
+//! # Defaults for every job and for the New Job popup
+//! shell = true
+//! # Also run after the terminal's clipboard escape, "auto" picks one
+//! clipboard = ["wl-copy"]
+//!
+//! [[job]]
+//! title = "web"
+//! cmd = "npm run dev"
+//! cwd = "frontend"
+//! env = { PORT = 3000 }
+//! autostart = true
+//! restart = "on-failure"
+//! tags = ["frontend"]
+//! ```
+//!
+//! `restart` also takes a table with `policy`, `max_retries` (a number, or
+//! `"unlimited"` to retry forever), `backoff` and `max_backoff`.
+//!
+//! Relative paths are relative to the directory of the file. Changes to the
+//! file are applied while running (see [`watch`]), and the current jobs can be
+//! written back to it (see [`export`]).
+
+pub mod export;
+pub mod watch;
+
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use thiserror::Error;
+use toml::Spanned;
+
+use crate::clipboard::Fallback;
+use crate::job::graph::{self, DependencyError};
+use crate::job::shell::Shell;
+use crate::job::{self, Job, ReadyCheck, Restart, RestartPolicy};
+
+/// Name of the project file, looked up from the current directory upwards
+pub const FILE_NAME: &str = "buj.toml";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Cannot read {}: {source}", short(path))]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}:{line}:{column}: {message}", short(path))]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    #[error("{}: {source}", short(path))]
+    Dependency {
+        path: PathBuf,
+        source: DependencyError,
+    },
+}
+
+/// `path` relative to the current directory when it's inside of it
+fn short(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+/// Contents of a project file
+pub struct Config {
+    pub path: PathBuf,
+    /// Shell of the jobs created from the New Job popup
+    pub default_shell: Option<Shell>,
+    pub clipboard: Option<Fallback>,
+    pub jobs: Vec<Job>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    shell: Option<ShellSpec>,
+    clipboard: Option<Spanned<ClipboardSpec>>,
+    #[serde(default, rename = "job")]
+    jobs: Vec<JobSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JobSpec {
+    title: Option<Spanned<String>>,
+    cmd: Spanned<String>,
+    cwd: Option<PathBuf>,
+    #[serde(default)]
+    env: BTreeMap<String, Spanned<toml::Value>>,
+    #[serde(default)]
+    clean_env: bool,
+    shell: Option<ShellSpec>,
+    #[serde(default)]
+    autostart: bool,
+    restart: Option<Spanned<RestartSpec>>,
+    scrollback: Option<usize>,
+    stop_signal: Option<Spanned<SignalSpec>>,
+    stop_timeout: Option<Spanned<DurationSpec>>,
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    stop_with_deps: bool,
+    ready: Option<Spanned<String>>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// `true` uses `$SHELL`, `false` runs the command directly, a string is the
+/// shell program
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "expected a boolean, a shell program or a table with `program` and `login`"
+)]
+enum ShellSpec {
+    Enabled(bool),
+    Program(PathBuf),
+    #[serde(rename_all = "snake_case")]
+    Table {
+        program: Option<PathBuf>,
+        #[serde(default)]
+        login: bool,
+    },
+}
+
+impl ShellSpec {
+    fn into_shell(self) -> Option<Shell> {
+        match self {
+            Self::Enabled(false) => None,
+            Self::Enabled(true) => Some(Shell::default()),
+            Self::Program(program) => Some(Shell {
+                program: Some(program),
+                login: false,
+            }),
+            Self::Table { program, login } => Some(Shell { program, login }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "expected a restart policy or a table with `policy`, `max_retries`, `backoff` and `max_backoff`"
+)]
+enum RestartSpec {
+    Policy(String),
+    Table {
+        policy: String,
+        max_retries: Option<RetriesSpec>,
+        backoff: Option<DurationSpec>,
+        max_backoff: Option<DurationSpec>,
+    },
+}
+
+/// Clipboard command, or `"auto"` to use the first one found
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "expected a command or \"auto\"")]
+enum ClipboardSpec {
+    Command(Vec<String>),
+    Text(String),
+}
+
+impl ClipboardSpec {
+    fn parse(&self) -> Option<Fallback> {
+        match self {
+            Self::Command(command) => Some(Fallback::Command(command.clone())),
+            Self::Text(text) if text == "auto" => Some(Fallback::Detect),
+            Self::Text(_) => None,
+        }
+    }
+}
+
+/// Number of restarts, or `"unlimited"`
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "expected a number of retries or \"unlimited\"")]
+enum RetriesSpec {
+    Count(u32),
+    Text(String),
+}
+
+impl RetriesSpec {
+    /// `Some(None)` retries forever
+    fn parse(&self) -> Option<Option<u32>> {
+        match self {
+            Self::Count(count) => Some(Some(*count)),
+            Self::Text(text) if text == "unlimited" => Some(None),
+            Self::Text(_) => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged, expecting = "expected a signal name or number")]
+enum SignalSpec {
+    Number(i32),
+    Name(String),
+}
+
+/// Seconds, or a number followed by `ms`, `s`, `m` or `h`
+#[derive(Deserialize)]
+#[serde(
+    untagged,
+    expecting = "expected a duration like `500ms`, `5s` or `2m`, or seconds"
+)]
+enum DurationSpec {
+    Secs(u64),
+    Text(String),
+}
+
+impl DurationSpec {
+    fn parse(&self) -> Option<Duration> {
+        let text = match self {
+            Self::Secs(secs) => return Some(Duration::from_secs(*secs)),
+            Self::Text(text) => text.trim(),
+        };
+
+        let split = text
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(text.len());
+        let (value, unit) = text.split_at(split);
+        let value: f64 = value.parse().ok()?;
+
+        let secs = match unit.trim() {
+            "ms" => value / 1000.0,
+            "" | "s" => value,
+            "m" => value * 60.0,
+            "h" => value * 3600.0,
+            _ => return None,
+        };
+
+        Duration::try_from_secs_f64(secs).ok()
+    }
+}
+
+/// Error on a part of the file
+struct SpanError {
+    span: Range<usize>,
+    message: String,
+}
+
+impl SpanError {
+    fn new(span: Range<usize>, message: impl ToString) -> Self {
+        Self {
+            span,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Look for [`FILE_NAME`] in the current directory and its parents
+pub fn find() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+
+    cwd.ancestors()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+pub fn load(path: &Path) -> Result<Config, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    parse(&text, path)
+}
+
+/// Contents of the project file at `path`
+fn parse(text: &str, path: &Path) -> Result<Config, ConfigError> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let to_error = |error: SpanError| {
+        let (line, column) = line_column(text, error.span.start);
+
+        ConfigError::Parse {
+            path: path.to_owned(),
+            line,
+            column,
+            message: error.message,
+        }
+    };
+
+    let file: ConfigFile = toml::from_str(text).map_err(|err| {
+        let message = err.message().trim_end();
+
+        // Drop the list of every known field
+        let message = match message.split_once(", expected one of") {
+            Some((unknown, _)) if message.starts_with("unknown field") => unknown,
+            _ => message,
+        };
+
+        to_error(SpanError::new(err.span().unwrap_or_default(), message))
+    })?;
+
+    let default_shell = file.shell.and_then(ShellSpec::into_shell);
+
+    let clipboard = file
+        .clipboard
+        .map(|spec| {
+            spec.get_ref()
+                .parse()
+                .ok_or_else(|| SpanError::new(spec.span(), "expected a command or `auto`"))
+        })
+        .transpose()
+        .map_err(to_error)?;
+
+    // Jobs are matched by title on reload, for dependencies and filters
+    let mut titles = HashSet::new();
+
+    let jobs = file
+        .jobs
+        .into_iter()
+        .map(|spec| {
+            // Untitled jobs are titled by their command
+            let span = spec
+                .title
+                .as_ref()
+                .map_or_else(|| spec.cmd.span(), Spanned::span);
+            let job = build_job(spec, dir, default_shell.as_ref())?;
+
+            if !titles.insert(job.title.clone()) {
+                return Err(SpanError::new(
+                    span,
+                    format!("another job is already titled `{}`", job.title),
+                ));
+            }
+
+            Ok(job)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(to_error)?;
+
+    graph::start_order(&jobs).map_err(|source| ConfigError::Dependency {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    Ok(Config {
+        path: path.to_owned(),
+        default_shell,
+        clipboard,
+        jobs,
+    })
+}
+
+/// Jobs of a file to load, from `--only` and `--except`
+#[derive(Default)]
+pub struct JobFilter {
+    /// Titles or tags of the jobs to load, empty loads every job
+    pub only: Vec<String>,
+    /// Titles or tags of the jobs not to load
+    pub except: Vec<String>,
+}
+
+impl JobFilter {
+    pub fn is_empty(&self) -> bool {
+        self.only.is_empty() && self.except.is_empty()
+    }
+
+    fn matches(names: &[String], job: &Job) -> bool {
+        names
+            .iter()
+            .any(|name| *name == job.title || job.tags.contains(name))
+    }
+
+    /// Keep the selected jobs, and the jobs they depend on so they can start
+    pub fn apply(&self, mut jobs: Vec<Job>) -> Vec<Job> {
+        if self.is_empty() {
+            return jobs;
+        }
+
+        let mut keep = vec![false; jobs.len()];
+        let mut pending: Vec<usize> = (0..jobs.len())
+            .filter(|&idx| {
+                let job = &jobs[idx];
+                (self.only.is_empty() || Self::matches(&self.only, job))
+                    && !Self::matches(&self.except, job)
+            })
+            .collect();
+
+        while let Some(idx) = pending.pop() {
+            if !std::mem::replace(&mut keep[idx], true) {
+                pending.extend(graph::dependencies(&jobs, idx).unwrap_or_default());
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        jobs.retain(|_| keep.next().unwrap_or(false));
+        jobs
+    }
+
+    /// Names of the filter matching none of `jobs`
+    pub fn unknown<'a>(&'a self, jobs: &[Job]) -> Vec<&'a str> {
+        self.only
+            .iter()
+            .chain(&self.except)
+            .filter(|name| {
+                !jobs
+                    .iter()
+                    .any(|job| Self::matches(std::slice::from_ref(name), job))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// What reloading the file did to a job
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum JobChange {
+    Unchanged,
+    /// Settings that apply without restarting (policy, dependencies...)
+    Updated,
+    /// How the process is spawned changed, a running job must restart
+    Respawn,
+}
+
+/// Take the declared fields of `new` into `job`, keeping its runtime state
+pub fn update_job(job: &mut Job, new: Job) -> JobChange {
+    let respawn = job.cmd != new.cmd
+        || job.cwd != new.cwd
+        || job.env != new.env
+        || job.clean_env != new.clean_env
+        || job.shell != new.shell;
+
+    let updated = job.autostart != new.autostart
+        || job.restart != new.restart
+        || job.scrollback != new.scrollback
+        || job.stop_signal != new.stop_signal
+        || job.stop_timeout != new.stop_timeout
+        || job.depends_on != new.depends_on
+        || job.tags != new.tags
+        || job.stop_with_deps != new.stop_with_deps
+        || job.ready_check.as_ref().map(ToString::to_string)
+            != new.ready_check.as_ref().map(ToString::to_string);
+
+    job.cmd = new.cmd;
+    job.cwd = new.cwd;
+    job.env = new.env;
+    job.clean_env = new.clean_env;
+    job.shell = new.shell;
+    job.autostart = new.autostart;
+    job.restart = new.restart;
+    job.scrollback = new.scrollback;
+    job.stop_signal = new.stop_signal;
+    job.stop_timeout = new.stop_timeout;
+    job.depends_on = new.depends_on;
+    job.tags = new.tags;
+    job.stop_with_deps = new.stop_with_deps;
+    job.ready_check = new.ready_check;
+
+    if respawn {
+        JobChange::Respawn
+    } else if updated {
+        JobChange::Updated
+    } else {
+        JobChange::Unchanged
+    }
+}
+
+/// 1-based line and column of a byte offset
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+fn build_job(spec: JobSpec, dir: &Path, default_shell: Option<&Shell>) -> Result<Job, SpanError> {
+    if spec.cmd.get_ref().trim().is_empty() {
+        return Err(SpanError::new(spec.cmd.span(), "`cmd` can't be empty"));
+    }
+
+    let mut job = Job::new(spec.cmd.get_ref());
+    job.from_config = true;
+
+    if let Some(title) = spec.title {
+        if title.get_ref().trim().is_empty() {
+            return Err(SpanError::new(title.span(), "`title` can't be empty"));
+        }
+
+        job.title = title.into_inner();
+    }
+
+    job.cwd = Some(match spec.cwd {
+        Some(cwd) => dir.join(cwd),
+        None => dir.to_owned(),
+    });
+
+    job.env = spec
+        .env
+        .into_iter()
+        .map(|(key, value)| {
+            let span = value.span();
+
+            let value = match value.into_inner() {
+                toml::Value::String(value) => value,
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Float(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                _ => return Err(SpanError::new(span, "expected a string, number or boolean")),
+            };
+
+            Ok((key, value))
+        })
+        .collect::<Result<_, _>>()?;
+
+    job.clean_env = spec.clean_env;
+    job.shell = match spec.shell {
+        Some(shell) => shell.into_shell(),
+        None => default_shell.cloned(),
+    };
+    job.autostart = spec.autostart;
+
+    if let Some(restart) = spec.restart {
+        job.restart = build_restart(&restart)?;
+    }
+
+    if let Some(scrollback) = spec.scrollback {
+        job.scrollback = scrollback;
+    }
+
+    if let Some(signal) = spec.stop_signal {
+        let parsed = match signal.get_ref() {
+            SignalSpec::Number(number) => job::parse_signal(&number.to_string()),
+            SignalSpec::Name(name) => job::parse_signal(name),
+        };
+
+        job.stop_signal = parsed.ok_or_else(|| SpanError::new(signal.span(), "unknown signal"))?;
+    }
+
+    if let Some(timeout) = spec.stop_timeout {
+        job.stop_timeout = parse_duration(&timeout)?;
+    }
+
+    job.depends_on = spec.depends_on;
+    job.tags = spec.tags;
+    job.stop_with_deps = spec.stop_with_deps;
+
+    if let Some(ready) = spec.ready {
+        job.ready_check = Some(
+            ready
+                .get_ref()
+                .parse::<ReadyCheck>()
+                .map_err(|err| SpanError::new(ready.span(), err))?,
+        );
+    }
+
+    Ok(job)
+}
+
+fn build_restart(spec: &Spanned<RestartSpec>) -> Result<Restart, SpanError> {
+    let error = |err: &dyn std::fmt::Display| SpanError::new(spec.span(), err);
+
+    match spec.get_ref() {
+        RestartSpec::Policy(policy) => Ok(Restart {
+            policy: policy.parse::<RestartPolicy>().map_err(|err| error(&err))?,
+            ..Restart::default()
+        }),
+        RestartSpec::Table {
+            policy,
+            max_retries,
+            backoff,
+            max_backoff,
+        } => {
+            let defaults = Restart::default();
+            let duration = |duration: &Option<DurationSpec>, default| match duration {
+                Some(duration) => duration.parse().ok_or_else(|| error(&"invalid duration")),
+                None => Ok(default),
+            };
+
+            let max_retries = match max_retries {
+                Some(retries) => retries
+                    .parse()
+                    .ok_or_else(|| error(&"expected a number of retries or `unlimited`"))?,
+                None => defaults.max_retries,
+            };
+
+            Ok(Restart {
+                policy: policy.parse::<RestartPolicy>().map_err(|err| error(&err))?,
+                max_retries,
+                backoff: duration(backoff, defaults.backoff)?,
+                max_backoff: duration(max_backoff, defaults.max_backoff)?,
+            })
+        }
+    }
+}
+
+fn parse_duration(spec: &Spanned<DurationSpec>) -> Result<Duration, SpanError> {
+    spec.get_ref()
+        .parse()
+        .ok_or_else(|| SpanError::new(spec.span(), "invalid duration"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "/project/buj.toml";
+
+    fn jobs(text: &str) -> Vec<Job> {
+        match parse(text, Path::new(PATH)) {
+            Ok(config) => config.jobs,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Line, column and message of the error of `text`
+    fn error(text: &str) -> (usize, usize, String) {
+        match parse(text, Path::new(PATH)) {
+            Err(ConfigError::Parse {
+                line,
+                column,
+                message,
+                ..
+            }) => (line, column, message),
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("parsed without errors"),
+        }
+    }
+
+    fn assert_error(text: &str, line: usize, column: usize, message: &str) {
+        let (got_line, got_column, got_message) = error(text);
+
+        assert_eq!((got_line, got_column), (line, column), "{got_message}");
+        assert!(
+            got_message.contains(message),
+            "`{got_message}` doesn't contain `{message}`"
+        );
+    }
+
+    #[test]
+    fn parse_jobs() {
+        let jobs = jobs(
+            r#"
+shell = true
+
+[[job]]
+title = "web"
+cmd = "npm run dev"
+cwd = "frontend"
+env = { PORT = 3000, DEBUG = true }
+restart = { policy = "always", max_retries = "unlimited", backoff = "500ms" }
+stop_signal = "int"
+stop_timeout = 10
+tags = ["frontend"]
+
+[[job]]
+cmd = "cargo run"
+shell = false
+depends_on = ["web"]
+stop_signal = 15
+stop_timeout = "1.5s"
+"#,
+        );
+
+        let [web, api] = jobs.as_slice() else {
+            panic!("expected 2 jobs");
+        };
+
+        assert_eq!(web.title, "web");
+        assert_eq!(web.cwd.as_deref(), Some(Path::new("/project/frontend")));
+        assert_eq!(
+            web.env,
+            [
+                ("DEBUG".to_owned(), "true".to_owned()),
+                ("PORT".to_owned(), "3000".to_owned())
+            ]
+        );
+        assert!(web.shell.is_some());
+        assert_eq!(web.restart.policy, RestartPolicy::Always);
+        assert_eq!(web.restart.max_retries, None);
+        assert_eq!(web.restart.backoff, Duration::from_millis(500));
+        assert_eq!(web.restart.max_backoff, Restart::default().max_backoff);
+        assert_eq!(web.stop_signal, rustix::process::Signal::INT);
+        assert_eq!(web.stop_timeout, Duration::from_secs(10));
+        assert_eq!(web.tags, ["frontend"]);
+        assert!(web.from_config);
+
+        assert_eq!(api.title, "cargo run");
+        assert_eq!(api.cwd.as_deref(), Some(Path::new("/project")));
+        assert!(api.shell.is_none());
+        assert_eq!(api.depends_on, ["web"]);
+        assert_eq!(api.stop_signal, rustix::process::Signal::TERM);
+        assert_eq!(api.stop_timeout, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn durations() {
+        let parse = |text: &str| DurationSpec::Text(text.to_owned()).parse();
+
+        assert_eq!(DurationSpec::Secs(3).parse(), Some(Duration::from_secs(3)));
+        assert_eq!(parse("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse(" 2 s "), Some(Duration::from_secs(2)));
+        assert_eq!(parse("2m"), Some(Duration::from_secs(120)));
+        assert_eq!(parse("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse("5d"), None);
+        assert_eq!(parse("ms"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert_error("[[job]\ncmd = \"a\"", 1, 7, "expected `]`");
+        assert_error("[[job]]\ncmd = \"a\"\ncmd = \"b\"", 3, 1, "duplicate key");
+    }
+
+    #[test]
+    fn unknown_keys() {
+        assert_error(
+            "[[job]]\ncmd = \"a\"\n  titel = \"b\"",
+            3,
+            3,
+            "unknown field `titel`",
+        );
+        assert_error("shel = true", 1, 1, "unknown field `shel`");
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_error("[[job]]\ntitle = \"a\"", 1, 1, "missing field `cmd`");
+        assert_error("[[job]]\ncmd = \" \"", 2, 7, "`cmd` can't be empty");
+        assert_error(
+            "[[job]]\ncmd = \"a\"\ntitle = \"\"",
+            3,
+            9,
+            "`title` can't be empty",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nenv = { A = [1] }",
+            3,
+            13,
+            "expected a string",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nstop_signal = \"SIGNOPE\"",
+            3,
+            15,
+            "unknown signal",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nstop_signal = 99",
+            3,
+            15,
+            "unknown signal",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nstop_timeout = \"5 days\"",
+            3,
+            16,
+            "invalid duration",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nrestart = \"sometimes\"",
+            3,
+            11,
+            "Unknown restart policy `sometimes`",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nrestart = { policy = \"always\", max_retries = \"lots\" }",
+            3,
+            11,
+            "expected a number of retries or `unlimited`",
+        );
+        assert_error(
+            "[[job]]\ncmd = \"a\"\nready = \"nope\"",
+            3,
+            9,
+            "Unknown readiness check `nope`",
+        );
+        assert_error(
+            "clipboard = \"xclip\"",
+            1,
+            13,
+            "expected a command or `auto`",
+        );
+    }
+
+    #[test]
+    fn duplicate_titles() {
+        assert_error(
+            "[[job]]\ntitle = \"web\"\ncmd = \"a\"\n\n[[job]]\ntitle = \"web\"\ncmd = \"b\"",
+            6,
+            9,
+            "another job is already titled `web`",
+        );
+
+        // Untitled jobs are titled by their command
+        assert_error(
+            "[[job]]\ncmd = \"make\"\n\n[[job]]\ncmd = \"make\"",
+            5,
+            7,
+            "another job is already titled `make`",
+        );
+    }
+
+    #[test]
+    fn dependency_errors() {
+        let text = "[[job]]\ncmd = \"a\"\ndepends_on = [\"b\"]\n\n[[job]]\ncmd = \"b\"\ndepends_on = [\"a\"]";
+
+        assert!(matches!(
+            parse(text, Path::new(PATH)),
+            Err(ConfigError::Dependency {
+                source: DependencyError::Cycle(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn clipboard() {
+        let clipboard = |text: &str| parse(text, Path::new(PATH)).ok().unwrap().clipboard;
+
+        assert_eq!(clipboard(""), None);
+        assert_eq!(clipboard("clipboard = \"auto\""), Some(Fallback::Detect));
+        assert_eq!(
+            clipboard("clipboard = [\"xclip\", \"-i\"]"),
+            Some(Fallback::Command(vec!["xclip".to_owned(), "-i".to_owned()]))
+        );
+    }
+
+    #[test]
+    fn filter_keeps_dependencies() {
+        let jobs = || {
+            jobs(
+                r#"
+[[job]]
+cmd = "db"
+
+[[job]]
+cmd = "api"
+depends_on = ["db"]
+tags = ["backend"]
+
+[[job]]
+cmd = "web"
+"#,
+            )
+        };
+        let titles = |jobs: Vec<Job>| jobs.into_iter().map(|job| job.title).collect::<Vec<_>>();
+
+        let only = JobFilter {
+            only: vec!["backend".to_owned()],
+            except: Vec::new(),
+        };
+        assert_eq!(titles(only.apply(jobs())), ["db", "api"]);
+
+        let except = JobFilter {
+            only: Vec::new(),
+            except: vec!["web".to_owned()],
+        };
+        assert_eq!(titles(except.apply(jobs())), ["db", "api"]);
+
+        let unknown = JobFilter {
+            only: vec!["web".to_owned(), "docs".to_owned()],
+            except: Vec::new(),
+        };
+        assert_eq!(unknown.unknown(&jobs()), ["docs"]);
+    }
+
+    #[test]
+    fn update_job_changes() {
+        let load = |extra: &str| {
+            jobs(&format!(
+                "[[job]]\ntitle = \"web\"\ncmd = \"serve\"\n{extra}"
+            ))
+            .pop()
+            .unwrap()
+        };
+
+        let mut job = load("");
+        assert!(update_job(&mut job, load("")) == JobChange::Unchanged);
+
+        assert!(update_job(&mut job, load("restart = \"always\"")) == JobChange::Updated);
+        assert_eq!(job.restart.policy, RestartPolicy::Always);
+
+        assert!(update_job(&mut job, load("env = { A = 1 }")) == JobChange::Respawn);
+        assert_eq!(job.env, [("A".to_owned(), "1".to_owned())]);
+        assert_eq!(job.restart.policy, RestartPolicy::default());
+    }
+}
@@ -0,0 +1,422 @@
+//! Writing the jobs back as a project file, the inverse of [`super::load`].
+//!
+//! Only what differs from the defaults is written, so the file reads like one
+//! written by hand.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::clipboard::Fallback;
+use crate::job::shell::Shell;
+use crate::job::{self, Job, Restart};
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("Two jobs are titled `{0}`, rename one before exporting")]
+    DuplicateTitle(String),
+    #[error("Cannot export the jobs: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+#[derive(Serialize)]
+struct ExportFile<'a> {
+    shell: Option<ShellValue>,
+    clipboard: Option<ClipboardValue<'a>>,
+    job: Vec<ExportJob>,
+}
+
+#[derive(Serialize)]
+struct ExportJob {
+    title: String,
+    cmd: String,
+    cwd: Option<PathBuf>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "is_false")]
+    clean_env: bool,
+    shell: Option<ShellValue>,
+    #[serde(skip_serializing_if = "is_false")]
+    autostart: bool,
+    restart: Option<RestartValue>,
+    scrollback: Option<usize>,
+    stop_signal: Option<String>,
+    stop_timeout: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    stop_with_deps: bool,
+    ready: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+/// Written like the `shell` values [`super::load`] accepts
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ShellValue {
+    Enabled(bool),
+    Program(PathBuf),
+    Table {
+        program: Option<PathBuf>,
+        login: bool,
+    },
+}
+
+impl ShellValue {
+    fn new(shell: Option<&Shell>) -> Self {
+        match shell {
+            None => Self::Enabled(false),
+            Some(shell) if *shell == Shell::default() => Self::Enabled(true),
+            Some(Shell {
+                program: Some(program),
+                login: false,
+            }) => Self::Program(program.clone()),
+            Some(shell) => Self::Table {
+                program: shell.program.clone(),
+                login: shell.login,
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RestartValue {
+    Policy(String),
+    Table {
+        policy: String,
+        max_retries: RetriesValue,
+        backoff: String,
+        max_backoff: String,
+    },
+}
+
+impl RestartValue {
+    fn new(restart: &Restart) -> Option<Self> {
+        let policy_only = Restart {
+            policy: restart.policy,
+            ..Restart::default()
+        };
+        let policy = restart.policy.to_string();
+
+        if *restart == Restart::default() {
+            None
+        } else if *restart == policy_only {
+            Some(Self::Policy(policy))
+        } else {
+            Some(Self::Table {
+                policy,
+                max_retries: restart
+                    .max_retries
+                    .map_or(RetriesValue::Unlimited("unlimited"), RetriesValue::Count),
+                backoff: duration(restart.backoff),
+                max_backoff: duration(restart.max_backoff),
+            })
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ClipboardValue<'a> {
+    Command(&'a [String]),
+    Auto(&'static str),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RetriesValue {
+    Count(u32),
+    Unlimited(&'static str),
+}
+
+#[expect(clippy::trivially_copy_pass_by_ref, reason = "serde's signature")]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn duration(duration: Duration) -> String {
+    if duration.subsec_nanos() == 0 {
+        format!("{}s", duration.as_secs())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+/// `cwd` of a job as written in a file of `dir`: relative when it's inside
+/// of `dir`, `None` for `dir` itself
+fn relative_cwd(cwd: Option<&PathBuf>, dir: &Path) -> Option<PathBuf> {
+    let cwd = job::env::resolve_cwd(cwd)?;
+
+    match cwd.strip_prefix(dir) {
+        Ok(relative) if relative.as_os_str().is_empty() => None,
+        Ok(relative) => Some(relative.to_owned()),
+        Err(_) => Some(cwd),
+    }
+}
+
+fn export_job(job: &Job, dir: &Path, default_shell: Option<&Shell>) -> ExportJob {
+    let defaults = Job::new(&"");
+
+    ExportJob {
+        title: job.title.clone(),
+        cmd: job.cmd.clone(),
+        cwd: relative_cwd(job.cwd.as_ref(), dir),
+        env: job.env.iter().cloned().collect(),
+        clean_env: job.clean_env,
+        shell: (job.shell.as_ref() != default_shell).then(|| ShellValue::new(job.shell.as_ref())),
+        autostart: job.autostart,
+        restart: RestartValue::new(&job.restart),
+        scrollback: (job.scrollback != defaults.scrollback).then_some(job.scrollback),
+        stop_signal: (job.stop_signal != defaults.stop_signal)
+            .then(|| job::signal_name(job.stop_signal.as_raw()))
+            .flatten()
+            .map(str::to_owned),
+        stop_timeout: (job.stop_timeout != defaults.stop_timeout)
+            .then(|| duration(job.stop_timeout)),
+        depends_on: job.depends_on.clone(),
+        stop_with_deps: job.stop_with_deps,
+        ready: job.ready_check.as_ref().map(ToString::to_string),
+        tags: job.tags.clone(),
+    }
+}
+
+/// Contents of a project file in `dir` declaring `jobs`
+pub fn render(
+    jobs: &[Job],
+    dir: &Path,
+    default_shell: Option<&Shell>,
+    clipboard: Option<&Fallback>,
+) -> Result<String, ExportError> {
+    let mut titles = HashSet::new();
+
+    if let Some(job) = jobs.iter().find(|job| !titles.insert(&job.title)) {
+        return Err(ExportError::DuplicateTitle(job.title.clone()));
+    }
+
+    let file = ExportFile {
+        shell: default_shell.map(|shell| ShellValue::new(Some(shell))),
+        clipboard: clipboard.map(|fallback| match fallback {
+            Fallback::Detect => ClipboardValue::Auto("auto"),
+            Fallback::Command(command) => ClipboardValue::Command(command),
+        }),
+        job: jobs
+            .iter()
+            .map(|job| export_job(job, dir, default_shell))
+            .collect(),
+    };
+
+    Ok(toml::to_string(&file)?)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    Added,
+    Removed,
+}
+
+/// Line by line difference from `old` to `new`
+pub fn diff<'a>(old: &'a str, new: &'a str) -> Vec<(DiffKind, &'a str)> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // Longest common subsequence of the lines following each position
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((DiffKind::Same, old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            // Removed lines first, like diff tools do
+            lines.push((DiffKind::Removed, old[i]));
+            i += 1;
+        } else {
+            lines.push((DiffKind::Added, new[j]));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{self, JobChange};
+
+    const DIR: &str = "/project";
+
+    fn load(text: &str) -> config::Config {
+        match config::parse(text, &Path::new(DIR).join(config::FILE_NAME)) {
+            Ok(config) => config,
+            Err(err) => panic!("{err}\n{text}"),
+        }
+    }
+
+    /// Job started from the project directory
+    fn job(cmd: &str) -> Job {
+        let mut job = Job::new(&cmd);
+        job.cwd = Some(PathBuf::from(DIR));
+        job
+    }
+
+    fn export(jobs: &[Job]) -> String {
+        render(jobs, Path::new(DIR), None, None).unwrap()
+    }
+
+    fn changes(diff: &[(DiffKind, &str)]) -> Vec<(DiffKind, String)> {
+        diff.iter()
+            .filter(|(kind, _)| *kind != DiffKind::Same)
+            .map(|&(kind, line)| (kind, line.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn defaults_are_left_out() {
+        assert_eq!(
+            export(&[job("npm run dev")]),
+            "[[job]]\ntitle = \"npm run dev\"\ncmd = \"npm run dev\"\n"
+        );
+    }
+
+    #[test]
+    fn duplicate_titles_are_rejected() {
+        let jobs = [Job::new(&"make"), Job::new(&"make")];
+
+        assert!(matches!(
+            render(&jobs, Path::new(DIR), None, None),
+            Err(ExportError::DuplicateTitle(title)) if title == "make"
+        ));
+    }
+
+    #[test]
+    fn diff_of_unchanged_file() {
+        let text = "a\nb\nc\n";
+
+        assert!(
+            diff(text, text)
+                .iter()
+                .all(|(kind, _)| *kind == DiffKind::Same)
+        );
+        assert_eq!(diff(text, text).len(), 3);
+    }
+
+    #[test]
+    fn diff_of_added_job() {
+        let web = job("web");
+        let api = job("api");
+
+        let old = export(std::slice::from_ref(&web));
+        let new = export(&[web, api]);
+
+        assert_eq!(
+            changes(&diff(&old, &new)),
+            [
+                (DiffKind::Added, String::new()),
+                (DiffKind::Added, "[[job]]".to_owned()),
+                (DiffKind::Added, "title = \"api\"".to_owned()),
+                (DiffKind::Added, "cmd = \"api\"".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_of_changed_field() {
+        let mut web = job("web");
+        let old = export(std::slice::from_ref(&web));
+
+        web.autostart = true;
+        let new = export(&[web]);
+
+        assert_eq!(
+            changes(&diff(&old, &new)),
+            [(DiffKind::Added, "autostart = true".to_owned())]
+        );
+
+        let diff = diff(
+            "cmd = \"a\"\ntags = [\"x\"]\n",
+            "cmd = \"b\"\ntags = [\"x\"]\n",
+        );
+        assert_eq!(
+            changes(&diff),
+            [
+                (DiffKind::Removed, "cmd = \"a\"".to_owned()),
+                (DiffKind::Added, "cmd = \"b\"".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn export_loads_back_the_same_jobs() {
+        let config = load(
+            r#"
+shell = "/bin/zsh"
+clipboard = "auto"
+
+[[job]]
+title = "web"
+cmd = "npm run dev"
+cwd = "frontend"
+env = { PORT = 3000 }
+clean_env = true
+autostart = true
+restart = { policy = "always", max_retries = "unlimited", backoff = "500ms" }
+scrollback = 100
+stop_signal = "SIGINT"
+stop_timeout = "1500ms"
+depends_on = ["db"]
+stop_with_deps = true
+ready = "port:3000"
+tags = ["frontend"]
+
+[[job]]
+title = "db"
+cmd = "postgres -D data"
+shell = false
+restart = "on-failure"
+"#,
+        );
+
+        let text = render(
+            &config.jobs,
+            Path::new(DIR),
+            config.default_shell.as_ref(),
+            config.clipboard.as_ref(),
+        )
+        .unwrap();
+        let loaded = load(&text);
+
+        assert_eq!(loaded.default_shell, config.default_shell);
+        assert_eq!(loaded.clipboard, config.clipboard);
+        assert_eq!(loaded.jobs.len(), config.jobs.len());
+
+        for (mut job, new) in config.jobs.into_iter().zip(loaded.jobs) {
+            assert_eq!(job.title, new.title);
+            assert!(
+                config::update_job(&mut job, new) == JobChange::Unchanged,
+                "`{}` changed:\n{text}",
+                job.title
+            );
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Watching the project file for changes with inotify.
+//!
+//! The directory is watched instead of the file: editors often save by
+//! writing a new file and renaming it over the old one, which a watch on the
+//! file itself would lose track of.
+
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::time::Duration;
+
+use rustix::fd::OwnedFd;
+use rustix::fs::inotify::{self, CreateFlags, WatchFlags};
+use rustix::io::Errno;
+use tokio::io::Interest;
+use tokio::io::unix::AsyncFd;
+use tokio::sync::Notify;
+
+/// Notified when the project file changed, once the writes settled
+pub static CHANGED: Notify = Notify::const_new();
+
+/// Changes closer than this are reported once
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// Start watching `path`, notifying [`CHANGED`]
+pub fn spawn(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("not a file"))?
+        .to_owned();
+
+    let fd = inotify::init(CreateFlags::NONBLOCK | CreateFlags::CLOEXEC)?;
+    inotify::add_watch(&fd, dir, WatchFlags::CLOSE_WRITE | WatchFlags::MOVED_TO)?;
+    let fd = AsyncFd::with_interest(fd, Interest::READABLE)?;
+
+    tokio::spawn(async move {
+        while wait_change(&fd, &name).await.is_ok() {
+            // Wait until quiet, some tools save in several steps
+            loop {
+                match tokio::time::timeout(SETTLE, wait_change(&fd, &name)).await {
+                    Err(_elapsed) => break,
+                    Ok(Ok(())) => {}
+                    Ok(Err(_)) => return,
+                }
+            }
+
+            CHANGED.notify_one();
+        }
+    });
+
+    Ok(())
+}
+
+/// Wait for an event on the file `name` of the watched directory
+async fn wait_change(fd: &AsyncFd<OwnedFd>, name: &OsString) -> io::Result<()> {
+    let mut buf = [MaybeUninit::uninit(); 4096];
+
+    loop {
+        let mut guard = fd.readable().await?;
+        let mut reader = inotify::Reader::new(fd.get_ref(), &mut buf);
+        let mut changed = false;
+
+        loop {
+            match reader.next() {
+                Ok(event) => {
+                    changed |= event
+                        .file_name()
+                        .is_some_and(|file| OsStr::from_bytes(file.to_bytes()) == name);
+                }
+                Err(Errno::AGAIN) => {
+                    guard.clear_ready();
+                    break;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        if changed {
+            return Ok(());
+        }
+    }
+}
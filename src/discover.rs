@@ -0,0 +1,129 @@
+//! Finding runnable tasks in the manifests of a project: `package.json`
+//! scripts, Makefile targets, justfile recipes, Cargo binaries and examples,
+//! and VS Code tasks.
+
+mod cargo;
+mod just;
+mod make;
+mod npm;
+mod vscode;
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::job::Job;
+use crate::job::shell::Shell;
+
+/// Manifest a task was found in
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Npm,
+    Make,
+    Just,
+    Cargo,
+    VsCode,
+}
+
+impl Source {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Make => "make",
+            Self::Just => "just",
+            Self::Cargo => "cargo",
+            Self::VsCode => "vscode",
+        }
+    }
+}
+
+pub struct Task {
+    pub source: Source,
+    pub name: String,
+    pub cmd: String,
+    /// `None` runs from the scanned directory
+    pub cwd: Option<PathBuf>,
+    /// The command is a shell command line
+    pub shell: bool,
+}
+
+impl Task {
+    fn new(source: Source, name: impl ToString, cmd: impl ToString) -> Self {
+        Self {
+            source,
+            name: name.to_string(),
+            cmd: cmd.to_string(),
+            cwd: None,
+            shell: false,
+        }
+    }
+
+    /// Job running the task, with the shell of the New Job popup
+    pub fn to_job(&self, default_shell: Option<&Shell>) -> Job {
+        let mut job = Job::new(&self.cmd);
+        job.title.clone_from(&self.name);
+        job.cwd.clone_from(&self.cwd);
+        job.shell = default_shell
+            .cloned()
+            .or_else(|| self.shell.then(Shell::default));
+        job
+    }
+}
+
+/// A manifest that couldn't be read
+#[derive(Debug, Error)]
+#[error("{}: {message}", path.display())]
+pub struct DiscoverError {
+    path: PathBuf,
+    message: String,
+}
+
+impl DiscoverError {
+    fn new(path: &Path, message: impl ToString) -> Self {
+        Self {
+            path: path.to_owned(),
+            message: message.to_string(),
+        }
+    }
+}
+
+type Scanner = fn(&Path) -> Result<Vec<Task>, DiscoverError>;
+
+#[derive(Default)]
+pub struct Discovered {
+    pub tasks: Vec<Task>,
+    pub errors: Vec<DiscoverError>,
+}
+
+/// Tasks of every manifest found in `dir`, one manifest failing doesn't
+/// hide the tasks of the others
+pub fn scan(dir: &Path) -> Discovered {
+    let mut discovered = Discovered::default();
+
+    let scanners: [Scanner; 5] = [npm::scan, make::scan, just::scan, cargo::scan, vscode::scan];
+
+    for scan in scanners {
+        match scan(dir) {
+            Ok(tasks) => discovered.tasks.extend(tasks),
+            Err(err) => discovered.errors.push(err),
+        }
+    }
+
+    discovered
+}
+
+/// Contents of the first of `names` existing in `dir`
+fn read_any(dir: &Path, names: &[&str]) -> Result<Option<(PathBuf, String)>, DiscoverError> {
+    for name in names {
+        let path = dir.join(name);
+
+        match std::fs::read_to_string(&path) {
+            Ok(text) => return Ok(Some((path, text))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(DiscoverError::new(&path, err)),
+        }
+    }
+
+    Ok(None)
+}
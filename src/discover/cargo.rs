@@ -0,0 +1,137 @@
+//! Binaries and examples of the Cargo package, or of every member of the
+//! workspace.
+
+use std::path::{Path, PathBuf};
+
+use toml::Table;
+
+use super::{DiscoverError, Source, Task};
+
+pub fn scan(dir: &Path) -> Result<Vec<Task>, DiscoverError> {
+    let Some(manifest) = read_manifest(dir)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut packages = vec![dir.to_owned()];
+
+    if let Some(members) = manifest
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(|members| members.as_array())
+    {
+        packages.extend(
+            members
+                .iter()
+                .filter_map(|member| member.as_str())
+                .flat_map(|member| expand_member(dir, member)),
+        );
+    }
+
+    let mut tasks = Vec::new();
+
+    for package in packages {
+        let Some(manifest) = read_manifest(&package)? else {
+            continue;
+        };
+
+        let Some(name) = manifest
+            .get("package")
+            .and_then(|package| package.get("name"))
+            .and_then(|name| name.as_str())
+        else {
+            continue;
+        };
+
+        for (kind, target) in [("bin", "bin"), ("example", "example")] {
+            for target_name in targets(&package, &manifest, name, kind) {
+                tasks.push(Task::new(
+                    Source::Cargo,
+                    &target_name,
+                    format!("cargo run -p {name} --{target} {target_name}"),
+                ));
+            }
+        }
+    }
+
+    Ok(tasks)
+}
+
+fn read_manifest(dir: &Path) -> Result<Option<Table>, DiscoverError> {
+    let Some((path, text)) = super::read_any(dir, &["Cargo.toml"])? else {
+        return Ok(None);
+    };
+
+    text.parse::<Table>()
+        .map(Some)
+        .map_err(|err| DiscoverError::new(&path, err.message()))
+}
+
+/// Directories of a workspace member, which can end with a `*` glob
+fn expand_member(dir: &Path, member: &str) -> Vec<PathBuf> {
+    let Some(parent) = member.strip_suffix('*') else {
+        return vec![dir.join(member)];
+    };
+
+    let Ok(entries) = std::fs::read_dir(dir.join(parent)) else {
+        return Vec::new();
+    };
+
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.join("Cargo.toml").is_file())
+        .collect();
+
+    dirs.sort();
+    dirs
+}
+
+/// Declared and auto-discovered targets of `kind`, `bin` or `example`
+fn targets(dir: &Path, manifest: &Table, package: &str, kind: &str) -> Vec<String> {
+    let mut names: Vec<String> = manifest
+        .get(kind)
+        .and_then(|targets| targets.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|target| target.get("name")?.as_str())
+        .map(str::to_owned)
+        .collect();
+
+    let (main, targets_dir) = match kind {
+        "bin" => (dir.join("src/main.rs").is_file(), dir.join("src/bin")),
+        _ => (false, dir.join("examples")),
+    };
+
+    if main {
+        names.push(package.to_owned());
+    }
+
+    // `name.rs`, or `name/main.rs`
+    if let Ok(entries) = std::fs::read_dir(targets_dir) {
+        let mut found: Vec<String> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+
+                let is_target = if path.is_dir() {
+                    path.join("main.rs").is_file()
+                } else {
+                    path.extension().is_some_and(|ext| ext == "rs")
+                };
+
+                is_target.then(|| path.file_stem()?.to_str().map(str::to_owned))?
+            })
+            .collect();
+
+        found.sort();
+        names.extend(found);
+    }
+
+    let mut unique = Vec::new();
+
+    for name in names {
+        if !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+
+    unique
+}
@@ -0,0 +1,90 @@
+//! Public recipes of the justfile that can run without arguments.
+
+use std::path::Path;
+
+use super::{DiscoverError, Source, Task};
+
+const NAMES: [&str; 3] = ["justfile", "Justfile", ".justfile"];
+
+/// Lines starting with these words aren't recipes
+const KEYWORDS: [&str; 6] = ["set", "alias", "export", "import", "mod", "unexport"];
+
+pub fn scan(dir: &Path) -> Result<Vec<Task>, DiscoverError> {
+    let Some((_, text)) = super::read_any(dir, &NAMES)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut tasks = Vec::new();
+    let mut private = false;
+
+    for line in text.lines() {
+        if line.starts_with([' ', '\t', '#']) || line.trim().is_empty() {
+            continue;
+        }
+
+        // Attributes of the next recipe
+        if let Some(attributes) = line.strip_prefix('[') {
+            private |= attributes.contains("private");
+            continue;
+        }
+
+        let is_private = std::mem::take(&mut private);
+
+        let Some(head) = recipe_head(line) else {
+            continue;
+        };
+
+        let mut words = head.trim_start_matches('@').split_whitespace();
+
+        let Some(name) = words.next() else {
+            continue;
+        };
+
+        if KEYWORDS.contains(&name)
+            || is_private
+            || name.starts_with('_')
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            continue;
+        }
+
+        // Parameters without a default value must be given, `+args` too.
+        // Pieces of quoted defaults aren't parameters.
+        let required = words.any(|param| {
+            let param = param.trim_start_matches('$');
+
+            param.starts_with('+')
+                || (!param.is_empty()
+                    && param
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        });
+
+        if !required {
+            tasks.push(Task::new(Source::Just, name, format!("just {name}")));
+        }
+    }
+
+    Ok(tasks)
+}
+
+/// What's before the `:` of a recipe line, `None` for assignments. Default
+/// values of parameters are quoted and can contain `:`.
+fn recipe_head(line: &str) -> Option<&str> {
+    let mut quote = None;
+
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), _) if c == open => quote = None,
+            (None, ':') => {
+                return (!line[idx + 1..].starts_with('=')).then_some(&line[..idx]);
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
@@ -0,0 +1,50 @@
+//! Targets of the Makefile, the explicit ones only.
+
+use std::path::Path;
+
+use crate::job::shell;
+
+use super::{DiscoverError, Source, Task};
+
+/// Looked up in the order make does
+const NAMES: [&str; 3] = ["GNUmakefile", "makefile", "Makefile"];
+
+pub fn scan(dir: &Path) -> Result<Vec<Task>, DiscoverError> {
+    let Some((_, text)) = super::read_any(dir, &NAMES)? else {
+        return Ok(Vec::new());
+    };
+
+    let mut targets: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        // Recipes and comments
+        if line.starts_with([' ', '\t', '#']) {
+            continue;
+        }
+
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+
+        // Variables: `A := b`, `A ::= b`, `A = b:c`
+        if rest.starts_with('=') || rest.starts_with(":=") || head.contains('=') {
+            continue;
+        }
+
+        // Special targets (`.PHONY`), pattern rules and computed names
+        let names = head
+            .split_whitespace()
+            .filter(|name| !name.starts_with('.') && !name.contains(['%', '$']));
+
+        for name in names {
+            if !targets.contains(&name) {
+                targets.push(name);
+            }
+        }
+    }
+
+    Ok(targets
+        .into_iter()
+        .map(|name| Task::new(Source::Make, name, format!("make {}", shell::quote(name))))
+        .collect())
+}
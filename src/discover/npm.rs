@@ -0,0 +1,50 @@
+//! Scripts of `package.json`, run with the package manager of the lockfile.
+
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Map;
+
+use crate::job::shell;
+
+use super::{DiscoverError, Source, Task};
+
+/// Package manager used when the lockfile is found
+const LOCKFILES: [(&str, &str); 4] = [
+    ("pnpm-lock.yaml", "pnpm"),
+    ("yarn.lock", "yarn"),
+    ("bun.lock", "bun"),
+    ("bun.lockb", "bun"),
+];
+
+#[derive(Deserialize)]
+struct Package {
+    #[serde(default)]
+    scripts: Map<String, serde_json::Value>,
+}
+
+pub fn scan(dir: &Path) -> Result<Vec<Task>, DiscoverError> {
+    let Some((path, text)) = super::read_any(dir, &["package.json"])? else {
+        return Ok(Vec::new());
+    };
+
+    let package: Package =
+        serde_json::from_str(&text).map_err(|err| DiscoverError::new(&path, err))?;
+
+    let runner = LOCKFILES
+        .iter()
+        .find(|(lockfile, _)| dir.join(lockfile).is_file())
+        .map_or("npm", |(_, runner)| runner);
+
+    Ok(package
+        .scripts
+        .keys()
+        .map(|name| {
+            Task::new(
+                Source::Npm,
+                name,
+                format!("{runner} run {}", shell::quote(name)),
+            )
+        })
+        .collect())
+}
@@ -0,0 +1,160 @@
+//! Tasks of `.vscode/tasks.json`, the `shell`, `process` and `npm` ones.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::job::shell;
+
+use super::{DiscoverError, Source, Task};
+
+const TASKS_FILE: &str = ".vscode/tasks.json";
+
+pub fn scan(dir: &Path) -> Result<Vec<Task>, DiscoverError> {
+    let Some((path, text)) = super::read_any(dir, &[TASKS_FILE])? else {
+        return Ok(Vec::new());
+    };
+
+    let file: Value =
+        serde_json::from_str(&strip_jsonc(&text)).map_err(|err| DiscoverError::new(&path, err))?;
+
+    let tasks = file
+        .get("tasks")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|task| build_task(dir, task))
+        .collect();
+
+    Ok(tasks)
+}
+
+fn build_task(dir: &Path, task: &Value) -> Option<Task> {
+    let field = |name: &str| {
+        task.get("linux")
+            .and_then(|linux| linux.get(name))
+            .or_else(|| task.get(name))
+    };
+
+    let substitute = |text: &str| {
+        let folder = dir.to_string_lossy();
+        let basename = dir
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        text.replace("${workspaceFolder}", &folder)
+            .replace("${workspaceRoot}", &folder)
+            .replace("${workspaceFolderBasename}", &basename)
+            .replace("${cwd}", &folder)
+    };
+
+    let (cmd, shell) = match field("type").and_then(Value::as_str) {
+        Some("npm") => {
+            let script = field("script")?.as_str()?;
+            (format!("npm run {}", shell::quote(script)), false)
+        }
+        Some(kind @ ("shell" | "process")) => {
+            let shell = kind == "shell";
+            let command = substitute(string_value(field("command")?)?);
+
+            let args = field("args")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(string_value)
+                .map(|arg| shell::quote(&substitute(arg)));
+
+            // A shell command line is kept as written
+            let command = if shell {
+                command
+            } else {
+                shell::quote(&command)
+            };
+
+            (
+                std::iter::once(command)
+                    .chain(args)
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                shell,
+            )
+        }
+        _ => return None,
+    };
+
+    let name = field("label")
+        .and_then(Value::as_str)
+        .map_or_else(|| cmd.clone(), str::to_owned);
+
+    let cwd = field("options")
+        .and_then(|options| options.get("cwd"))
+        .and_then(Value::as_str)
+        .map(|cwd| PathBuf::from(substitute(cwd)));
+
+    Some(Task {
+        source: Source::VsCode,
+        name,
+        cmd,
+        cwd,
+        shell,
+    })
+}
+
+/// A plain string, or a `{ "value": ... }` one with a quoting style
+fn string_value(value: &Value) -> Option<&str> {
+    value.as_str().or_else(|| value.get("value")?.as_str())
+}
+
+/// JSON from the JSON with comments and trailing commas VS Code accepts
+fn strip_jsonc(text: &str) -> String {
+    let mut json = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            json.push(c);
+
+            match c {
+                '\\' => json.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                json.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            (']' | '}', _) => {
+                // Drop a trailing comma
+                let end = json.trim_end().len();
+
+                if json[..end].ends_with(',') {
+                    json.truncate(end - 1);
+                }
+
+                json.push(c);
+            }
+            _ => json.push(c),
+        }
+    }
+
+    json
+}
@@ -48,3 +48,19 @@ impl Drop for CaptureMouse {
         _ = crossterm::execute!(io::stdout(), crossterm::event::DisableMouseCapture);
     }
 }
+
+pub struct CapturePaste;
+
+impl CapturePaste {
+    pub fn scoped() -> io::Result<Self> {
+        crossterm::execute!(io::stdout(), crossterm::event::EnableBracketedPaste)?;
+
+        Ok(Self)
+    }
+}
+
+impl Drop for CapturePaste {
+    fn drop(&mut self) {
+        _ = crossterm::execute!(io::stdout(), crossterm::event::DisableBracketedPaste);
+    }
+}
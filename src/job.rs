@@ -1,16 +1,39 @@
-use std::io;
-use std::sync::Arc;
+pub mod env;
+pub mod graph;
+mod lines;
+pub mod log;
+mod output;
+mod process;
+mod ready;
+mod restart;
+mod search;
+pub mod shell;
+mod status;
+mod wait;
+
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
+use arc_swap::ArcSwap;
 use portable_pty::{MasterPty, PtyPair, native_pty_system};
 use ratatui::layout::Size;
-use rustix::process::Signal;
-use rustix::termios::Pid;
+use regex::Regex;
+use rustix::process::{Pid, Signal};
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, mpsc, oneshot};
+use tokio::time::Instant;
 use vt100::Parser;
 
-use crate::ui::Casted;
+use crate::keys::InputModes;
+use crate::vterm::Snapshot;
+use shell::Shell;
+
+pub use ready::ReadyCheck;
+pub use restart::{Restart, RestartPolicy};
+pub use status::{Exit, JobExit, JobStatus, parse_signal, signal_name};
 
 // tty spawn error messages
 const NOT_FOUND_MESSAGE: &str = "No viable candidates found in PATH";
@@ -18,6 +41,20 @@ const NOT_EXISTS_MESSAGE: &str = " it does not exist";
 const NOT_EXEC_MESSAGE: &str = " it is not executable";
 const IS_DIR_MESSAGE: &str = " it is a directory";
 
+/// Time given to a job to exit after the stop signal, before being killed
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Signals offered from the UI to ask a job to exit
+const STOP_SIGNALS: [Signal; 4] = [Signal::TERM, Signal::INT, Signal::HUP, Signal::QUIT];
+
+/// Lines of output kept above the screen
+pub const DEFAULT_SCROLLBACK: usize = 10_000;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Notified whenever any job changes its lifecycle state (exited, stopping...)
+pub static LIFECYCLE: Notify = Notify::const_new();
+
 #[derive(Debug, Error)]
 pub enum JobStartError {
     #[error("IO error: {0}")]
@@ -34,42 +71,307 @@ pub enum JobStartError {
     IsDir,
     #[error("Cannot parse command")]
     Parse(#[from] shellish_parse::ParseError),
+    #[error("Invalid environment variable `{0}`, expected KEY=VALUE")]
+    InvalidEnv(String),
+    #[error("Working directory does not exist")]
+    InvalidCwd,
 }
 
 pub struct JobRunning {
     pub pty: Box<dyn MasterPty + Send + 'static>,
-    pub vterm: Arc<RwLock<Parser>>,
+    pub writer: Box<dyn Write + Send + 'static>,
+    /// Latest screen published by the output reader
+    pub screen: Arc<ArcSwap<Snapshot>>,
+    control: mpsc::UnboundedSender<output::Control>,
     pub pid: u32,
-    pub status: Arc<RwLock<Option<u32>>>,
+    pub started_at: Instant,
+    /// Set once by the exit watcher
+    pub exit: Arc<OnceLock<JobExit>>,
+    /// When the stop signal was sent
+    pub stopping: Option<Instant>,
+    /// The exit was already seen by [`Job::update`]
+    exit_handled: bool,
+    /// The readiness check succeeded
+    pub ready: Arc<AtomicBool>,
+}
+
+impl JobRunning {
+    pub fn screen(&self) -> Arc<Snapshot> {
+        self.screen.load_full()
+    }
+
+    /// Resize the terminal of the job
+    pub fn resize(&self, size: Size) {
+        _ = self.pty.resize(portable_pty::PtySize {
+            rows: size.height,
+            cols: size.width,
+            pixel_width: 0,
+            pixel_height: 0,
+        });
+        _ = self.control.send(output::Control::Resize {
+            rows: size.height,
+            cols: size.width,
+        });
+    }
+
+    /// Scroll the view `delta` lines back, negative goes forward
+    pub fn scroll(&self, delta: isize) {
+        _ = self.control.send(output::Control::Scroll(delta));
+    }
+
+    /// Search `regex` on the output, `None` clears the search
+    pub fn search(&self, regex: Option<Regex>) {
+        _ = self.control.send(output::Control::Search(regex));
+    }
+
+    /// Select the next search match, `forward` goes towards newer output
+    pub fn search_step(&self, forward: bool) {
+        _ = self.control.send(output::Control::SearchStep(forward));
+    }
+
+    /// Scroll the least needed to make `line` visible (see [`Snapshot::top`])
+    pub fn reveal(&self, line: usize) {
+        _ = self.control.send(output::Control::Reveal(line));
+    }
+
+    /// Text between two `(line, col)` positions, both included
+    pub async fn text(&self, from: (usize, u16), to: (usize, u16)) -> Option<String> {
+        let (reply, text) = oneshot::channel();
+        self.control
+            .send(output::Control::Text { from, to, reply })
+            .ok()?;
+
+        text.await.ok()
+    }
+
+    /// Go back to the bottom of the output if scrolled back
+    pub fn follow(&self) {
+        if self.screen.load().scrollback > 0 {
+            _ = self.control.send(output::Control::Follow);
+        }
+    }
+
+    pub fn exit(&self) -> Option<Exit> {
+        self.exit.get().map(|exit| exit.exit)
+    }
+
+    pub fn exited(&self) -> bool {
+        self.exit.get().is_some()
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Relaxed)
+    }
+
+    /// Time since the process started until now or until it exited
+    pub fn runtime(&self) -> Duration {
+        let end = self.exit.get().map_or_else(Instant::now, |exit| exit.at);
+        end.saturating_duration_since(self.started_at)
+    }
 }
 
 pub struct Job {
+    /// Unique id during the session, can be used to reference the job
+    pub id: u64,
     pub title: String,
     pub cmd: String,
+    /// Working directory, relative to buj's one. `None` uses buj's one
+    pub cwd: Option<PathBuf>,
+    /// Variables added or overridden on top of the inherited environment
+    pub env: Vec<(String, String)>,
+    /// Don't inherit buj's environment (see [`env::CLEAN_ENV_KEEP`])
+    pub clean_env: bool,
+    /// Run [`Job::cmd`] through a shell, `None` executes it directly
+    pub shell: Option<Shell>,
+    /// Started as soon as it's loaded from the project file
+    pub autostart: bool,
+    /// Declared in the project file, reloading the file updates or removes it
+    pub from_config: bool,
+    /// Labels to select jobs with `--only` and `--except`
+    pub tags: Vec<String>,
     pub notify: Arc<tokio::sync::Notify>,
     pub running: Option<JobRunning>,
     pub size: Size,
+    /// Lines of output kept above the screen
+    pub scrollback: usize,
+    /// Signal sent to ask the job to exit
+    pub stop_signal: Signal,
+    /// Grace period after [`Job::stop_signal`] before escalating to `SIGKILL`
+    pub stop_timeout: Duration,
+    /// Start again as soon as the current process exits
+    pub pending_restart: bool,
+    pub restart: Restart,
+    /// Consecutive automatic restarts
+    pub retries: u32,
+    /// When the next automatic restart happens
+    pub next_retry: Option<Instant>,
+    /// Was stopped by the user, automatic restarts are suppressed
+    pub stopped: bool,
+    /// Titles or ids of the jobs that must be up before this one starts
+    pub depends_on: Vec<String>,
+    /// Stop this job when one of its dependencies exits, and start it again
+    /// once they are back
+    pub stop_with_deps: bool,
+    /// Will be started as soon as its dependencies are up
+    pub waiting_deps: bool,
+    /// Until it succeeds the job is considered as starting, without a check
+    /// the job is ready as soon as it's spawned
+    pub ready_check: Option<ReadyCheck>,
+    /// Runtime of all the previous runs
+    pub past_runtime: Duration,
+    /// Error of the last failed start
+    pub spawn_error: Option<String>,
 }
 
 impl Job {
     pub fn new(cmd: &impl ToString) -> Self {
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             title: cmd.to_string(),
             cmd: cmd.to_string(),
+            cwd: None,
+            env: Vec::new(),
+            clean_env: false,
+            shell: None,
+            autostart: false,
+            from_config: false,
+            tags: Vec::new(),
             notify: Arc::default(),
             running: None,
             size: Size::new(80, 24),
+            scrollback: DEFAULT_SCROLLBACK,
+            stop_signal: Signal::TERM,
+            stop_timeout: DEFAULT_STOP_TIMEOUT,
+            pending_restart: false,
+            restart: Restart::default(),
+            retries: 0,
+            next_retry: None,
+            stopped: false,
+            depends_on: Vec::new(),
+            stop_with_deps: false,
+            waiting_deps: false,
+            ready_check: None,
+            past_runtime: Duration::ZERO,
+            spawn_error: None,
+        }
+    }
+
+    /// The same job, not started yet
+    pub fn clone_definition(&self) -> Self {
+        Self {
+            id: self.id,
+            title: self.title.clone(),
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            clean_env: self.clean_env,
+            shell: self.shell.clone(),
+            autostart: self.autostart,
+            from_config: self.from_config,
+            tags: self.tags.clone(),
+            size: self.size,
+            scrollback: self.scrollback,
+            stop_signal: self.stop_signal,
+            stop_timeout: self.stop_timeout,
+            restart: self.restart.clone(),
+            depends_on: self.depends_on.clone(),
+            stop_with_deps: self.stop_with_deps,
+            ready_check: self.ready_check.clone(),
+            ..Self::new(&self.cmd)
         }
     }
 
-    // None means that is running or is not started
-    pub fn status(&self) -> Option<u32> {
+    /// Runtime of the current or last run
+    pub fn runtime(&self) -> Option<Duration> {
+        self.running.as_ref().map(JobRunning::runtime)
+    }
+
+    /// Runtime of all runs, including the current one
+    pub fn total_runtime(&self) -> Duration {
+        let current = self
+            .running
+            .as_ref()
+            .filter(|job| !job.exit_handled)
+            .map_or(Duration::ZERO, JobRunning::runtime);
+
+        self.past_runtime + current
+    }
+
+    /// Has a process that didn't exit yet
+    pub fn is_alive(&self) -> bool {
+        self.running.as_ref().is_some_and(|job| !job.exited())
+    }
+
+    /// Is alive and passed its readiness check
+    pub fn is_ready(&self) -> bool {
+        self.running
+            .as_ref()
+            .is_some_and(|job| job.is_ready() && !job.exited())
+    }
+
+    /// Is alive but didn't pass its readiness check yet
+    pub fn is_starting(&self) -> bool {
         self.running
             .as_ref()
-            .and_then(|r| *r.status.blocking_read())
+            .is_some_and(|job| !job.is_ready() && !job.exited())
+    }
+
+    /// Was asked to stop but is still alive
+    pub fn is_stopping(&self) -> bool {
+        self.running
+            .as_ref()
+            .is_some_and(|job| job.stopping.is_some() && !job.exited())
+    }
+
+    pub fn status(&self) -> JobStatus {
+        if let Some(ref err) = self.spawn_error {
+            return JobStatus::SpawnFailed(err.clone());
+        }
+
+        let Some(ref job) = self.running else {
+            return JobStatus::NotStarted;
+        };
+
+        match job.exit() {
+            Some(exit) => exit.into(),
+            None if job.stopping.is_some() => JobStatus::Stopping,
+            None => JobStatus::Running,
+        }
+    }
+
+    /// Check what can be checked of the definition without spawning it
+    pub fn validate(&self) -> Result<(), JobStartError> {
+        if self.shell.is_some() {
+            if self.cmd.trim().is_empty() {
+                return Err(JobStartError::NoCommand);
+            }
+        } else if shellish_parse::parse(&self.cmd, true)?.is_empty() {
+            return Err(JobStartError::NoCommand);
+        }
+
+        if let Some(cwd) = env::resolve_cwd(self.cwd.as_ref())
+            && !cwd.is_dir()
+        {
+            return Err(JobStartError::InvalidCwd);
+        }
+
+        Ok(())
     }
 
     pub fn start(&mut self) -> Result<(), JobStartError> {
+        let result = self.spawn();
+        self.spawn_error = result.as_ref().err().map(ToString::to_string);
+        result
+    }
+
+    fn spawn(&mut self) -> Result<(), JobStartError> {
+        // Account the previous run if its exit was not seen yet
+        if let Some(ref mut job) = self.running
+            && !job.exit_handled
+        {
+            job.exit_handled = true;
+            self.past_runtime += job.runtime();
+        }
+
         let pty = native_pty_system();
         let PtyPair { slave, master } = pty
             .openpty(portable_pty::PtySize {
@@ -80,17 +382,47 @@ impl Job {
             })
             .unwrap();
 
-        let mut parsed = shellish_parse::parse(&self.cmd, true)?.into_iter();
+        let mut cmd = if let Some(ref shell) = self.shell {
+            if self.cmd.trim().is_empty() {
+                return Err(JobStartError::NoCommand);
+            }
+
+            let mut cmd = portable_pty::CommandBuilder::new(shell.program());
+            cmd.args(shell.args(&self.cmd));
+            cmd
+        } else {
+            let mut parsed = shellish_parse::parse(&self.cmd, true)?.into_iter();
+
+            let program = parsed.next().ok_or(JobStartError::NoCommand)?;
 
-        let cmd = parsed.next().ok_or(JobStartError::NoCommand)?;
+            let mut cmd = portable_pty::CommandBuilder::new(program);
+            cmd.args(parsed);
+            cmd
+        };
+
+        if let Some(cwd) = env::resolve_cwd(self.cwd.as_ref()) {
+            if !cwd.is_dir() {
+                return Err(JobStartError::InvalidCwd);
+            }
 
-        let mut cmd = portable_pty::CommandBuilder::new(cmd);
-        cmd.args(parsed);
-        if let Ok(cwd) = std::env::current_dir() {
             cmd.cwd(cwd);
         }
 
-        let mut child = slave.spawn_command(cmd).map_err(|err| {
+        if self.clean_env {
+            cmd.env_clear();
+
+            for key in env::CLEAN_ENV_KEEP {
+                if let Some(value) = std::env::var_os(key) {
+                    cmd.env(key, value);
+                }
+            }
+        }
+
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+
+        let child = slave.spawn_command(cmd).map_err(|err| {
             let err = err.to_string();
             let Some(because_idx) = err.find("because") else {
                 unreachable!("malformed error (open an issue): {err}");
@@ -116,81 +448,269 @@ impl Job {
         })?;
         let pid = child.process_id().unwrap();
 
-        let status = Arc::new(RwLock::new(None));
+        let started_at = Instant::now();
+        let exit = Arc::new(OnceLock::new());
 
         tokio::task::spawn({
-            let status = status.clone();
+            let exit = exit.clone();
+            let notifier = self.notify.clone();
+            let wait_pid = Pid::from_raw(pid.cast_signed()).unwrap();
 
             async move {
-                loop {
-                    match child.try_wait() {
-                        Ok(None) => {}
-                        Ok(Some(s)) => {
-                            *status.write_owned().await = Some(s.exit_code());
-                            drop(slave);
-                            break;
-                        }
-                        Err(err) => {
-                            println!("Error waiting job child: {err}");
-                            break;
-                        }
-                    }
-
-                    tokio::time::sleep(Duration::from_millis(50)).await;
+                // Reaped there instead of with `child.wait()`, which loses the
+                // signal number and core dump flag
+                // Only fails if the child was reaped elsewhere, its exit is
+                // then unknown
+                if let Ok(status) = wait::wait_exit(wait_pid).await {
+                    _ = exit.set(JobExit {
+                        exit: Exit::from(&status),
+                        at: Instant::now(),
+                    });
                 }
+
+                drop(child);
+                drop(slave);
+                notifier.notify_one();
+                LIFECYCLE.notify_one();
             }
         });
 
-        let vterm = Arc::new(RwLock::new(Parser::new(
-            self.size.height,
-            self.size.width,
-            0,
-        )));
-        let vterm_ = vterm.clone();
-        let notifier = self.notify.clone();
-        let mut reader = master.try_clone_reader().unwrap();
-
-        tokio::spawn(async move {
-            let buf = &mut [0; 1024];
+        let parser = Parser::new(self.size.height, self.size.width, self.scrollback);
+        let screen = Arc::new(ArcSwap::from_pointee(Snapshot::new(parser.screen())));
+        let (control, control_rx) = mpsc::unbounded_channel();
+        let reader = master.try_clone_reader().unwrap();
+        let writer = master.take_writer().map_err(io::Error::other)?;
 
-            loop {
-                let Ok(size) = reader.read(buf) else {
-                    return;
-                };
+        let ready = Arc::new(AtomicBool::new(self.ready_check.is_none()));
+        let matcher = ready::OutputMatcher::new(self.ready_check.as_ref());
 
-                vterm_.write().await.process(&buf[0..size]);
+        if let Some(ref check) = self.ready_check {
+            ready::spawn_probe(check, ready.clone(), exit.clone());
+        }
 
-                notifier.notify_one();
-            }
-        });
+        output::spawn(
+            self.id,
+            reader,
+            output::OutputSink {
+                parser,
+                scrollback: self.scrollback,
+                screen: screen.clone(),
+                matcher,
+                ready: ready.clone(),
+                notify: self.notify.clone(),
+            },
+            control_rx,
+        )?;
 
         self.running = Some(JobRunning {
             pty: master,
-            vterm,
+            writer,
+            screen,
+            control,
             pid,
-            status,
+            started_at,
+            exit,
+            stopping: None,
+            exit_handled: false,
+            ready,
         });
+        self.stopped = false;
 
         Ok(())
     }
 
+    /// Immediately kill the job and all its descendants
     pub fn kill(&mut self) -> bool {
         let Some(ref job) = self.running else {
             return false;
         };
 
-        rustix::process::kill_process(
-            unsafe { Pid::from_raw_unchecked(job.pid.casted::<i32>()) },
-            Signal::KILL,
-        )
-        .is_ok()
+        // Once reaped, the pid can belong to another process
+        if job.exited() {
+            return process::signal_leftovers(job.pid, Signal::KILL);
+        }
+
+        process::signal_tree(job.pid, Signal::KILL)
+    }
+
+    /// Ask the job to exit with [`Job::stop_signal`], escalating to `SIGKILL`
+    /// after [`Job::stop_timeout`].
+    ///
+    /// Stopping a job that is already stopping kills it.
+    pub fn stop(&mut self) -> bool {
+        self.stopped = true;
+        self.next_retry = None;
+        self.waiting_deps = false;
+
+        let Some(ref mut job) = self.running else {
+            return false;
+        };
+
+        if job.exited() {
+            return false;
+        }
+
+        if job.stopping.is_some() {
+            return process::signal_tree(job.pid, Signal::KILL);
+        }
+
+        if !process::signal_tree(job.pid, self.stop_signal) {
+            return false;
+        }
+
+        job.stopping = Some(Instant::now());
+        LIFECYCLE.notify_one();
+
+        let pid = job.pid;
+        let exit = job.exit.clone();
+        let timeout = self.stop_timeout;
+
+        // Also reaches descendants that ignored the signal after the leader
+        // exited, without signaling the reaped pid that may have been reused
+        tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+
+            if exit.get().is_some() {
+                process::signal_leftovers(pid, Signal::KILL);
+            } else {
+                process::signal_tree(pid, Signal::KILL);
+            }
+        });
+
+        true
+    }
+
+    /// Next of the usual stop signals, used to cycle through them from the UI
+    pub fn next_stop_signal(&mut self) {
+        let idx = STOP_SIGNALS
+            .iter()
+            .position(|signal| *signal == self.stop_signal);
+
+        self.stop_signal = idx.map_or(STOP_SIGNALS[0], |idx| {
+            STOP_SIGNALS[(idx + 1) % STOP_SIGNALS.len()]
+        });
+    }
+
+    /// Input modes currently requested by the job
+    pub fn input_modes(&self) -> InputModes {
+        self.running
+            .as_ref()
+            .map_or_else(InputModes::default, |job| job.screen.load().modes)
+    }
+
+    /// Write raw input to the job's terminal
+    pub fn write_input(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let Some(ref mut job) = self.running else {
+            return Ok(());
+        };
+
+        job.follow();
+        job.writer.write_all(bytes)?;
+        job.writer.flush()
     }
 
+    /// Restart the job, if it's alive it will be started again once it
+    /// gracefully stops (see [`Job::update`])
     pub fn restart(&mut self) -> Result<(), JobStartError> {
-        self.kill();
+        self.retries = 0;
+        self.next_retry = None;
+
+        if self.restart_alive() {
+            return Ok(());
+        }
+
         self.start()
     }
 
+    /// Stop the job to start it again once it exits, does nothing if it's
+    /// not alive.
+    ///
+    /// Returns whether the job was alive.
+    pub fn restart_alive(&mut self) -> bool {
+        if !self.is_alive() {
+            return false;
+        }
+
+        self.pending_restart = true;
+        self.stop();
+        true
+    }
+
+    /// Drive pending lifecycle transitions
+    pub fn update(&mut self) -> Result<(), JobStartError> {
+        if self.pending_restart && !self.is_alive() {
+            self.pending_restart = false;
+            return self.start();
+        }
+
+        if let Some(at) = self.next_retry {
+            if Instant::now() < at {
+                return Ok(());
+            }
+
+            self.next_retry = None;
+            self.retries += 1;
+
+            let started = self.start();
+
+            // A failed start counts as a crash
+            if started.is_err() {
+                self.schedule_retry();
+            }
+
+            return started;
+        }
+
+        let Some(ref mut job) = self.running else {
+            return Ok(());
+        };
+
+        let Some(exit) = job.exit().filter(|_| !job.exit_handled) else {
+            return Ok(());
+        };
+
+        job.exit_handled = true;
+
+        let runtime = job.runtime();
+        self.past_runtime += runtime;
+
+        // Crashes far apart don't use up the retries
+        if runtime >= self.restart.max_backoff {
+            self.retries = 0;
+        }
+
+        if self.restart.policy.should_restart(exit, self.stopped) {
+            self.schedule_retry();
+        }
+
+        Ok(())
+    }
+
+    /// Plan the next automatic restart, unless the retries are exhausted
+    fn schedule_retry(&mut self) {
+        let Some(delay) = self.restart.delay(self.retries) else {
+            return;
+        };
+
+        let at = Instant::now() + delay;
+        self.next_retry = Some(at);
+
+        // Wake up every second to refresh the countdown, and at the deadline
+        tokio::spawn(async move {
+            loop {
+                let now = Instant::now();
+
+                if now >= at {
+                    LIFECYCLE.notify_one();
+                    return;
+                }
+
+                tokio::time::sleep_until(at.min(now + Duration::from_secs(1))).await;
+                LIFECYCLE.notify_one();
+            }
+        });
+    }
+
     pub fn with_cmd(&mut self, cmd: String) {
         self.cmd = cmd;
     }
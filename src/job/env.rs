@@ -0,0 +1,52 @@
+//! Environment of a job: working directory and variables.
+
+use std::path::PathBuf;
+
+use super::JobStartError;
+
+/// Variables kept when a job starts from a clean environment, without them
+/// commands cannot even be found in `PATH`
+pub const CLEAN_ENV_KEEP: [&str; 6] = ["PATH", "HOME", "USER", "SHELL", "TERM", "LANG"];
+
+/// Parse `KEY=VALUE` pairs separated by whitespace, values can be quoted
+pub fn parse_env(s: &str) -> Result<Vec<(String, String)>, JobStartError> {
+    shellish_parse::parse(s, true)?
+        .into_iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_owned(), value.to_owned())),
+            _ => Err(JobStartError::InvalidEnv(entry)),
+        })
+        .collect()
+}
+
+/// Inverse of [`parse_env`]
+pub fn format_env(env: &[(String, String)]) -> String {
+    env.iter()
+        .map(|(key, value)| {
+            let needs_quotes = value.is_empty()
+                || value
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | '#'));
+
+            if needs_quotes {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                format!("{key}=\"{value}\"")
+            } else {
+                format!("{key}={value}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Resolve the working directory of a job, relative paths are relative to
+/// the directory buj was started on
+pub fn resolve_cwd(cwd: Option<&PathBuf>) -> Option<PathBuf> {
+    let base = std::env::current_dir().ok();
+
+    match cwd {
+        Some(cwd) if cwd.is_absolute() => Some(cwd.clone()),
+        Some(cwd) => base.map(|base| base.join(cwd)),
+        None => base,
+    }
+}
@@ -0,0 +1,182 @@
+//! Dependencies between jobs.
+//!
+//! Jobs reference their dependencies by title or id, this module resolves
+//! those references and computes the order in which jobs must be started.
+
+use thiserror::Error;
+
+use super::Job;
+
+#[derive(Debug, Error)]
+pub enum DependencyError {
+    #[error("Job `{job}` depends on `{dependency}`, which does not exist")]
+    Unknown { job: String, dependency: String },
+    #[error("Dependency cycle: {}", .0.join(" → "))]
+    Cycle(Vec<String>),
+}
+
+impl Job {
+    /// Whether `name` references this job
+    pub fn is_named(&self, name: &str) -> bool {
+        self.title == name || self.id.to_string() == name
+    }
+}
+
+/// Indices of the direct dependencies of `jobs[idx]`
+pub fn dependencies(jobs: &[Job], idx: usize) -> Result<Vec<usize>, DependencyError> {
+    let job = &jobs[idx];
+
+    job.depends_on
+        .iter()
+        .map(|dependency| {
+            jobs.iter()
+                .position(|other| other.is_named(dependency))
+                .ok_or_else(|| DependencyError::Unknown {
+                    job: job.title.clone(),
+                    dependency: dependency.clone(),
+                })
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Unvisited,
+    Visiting,
+    Done,
+}
+
+/// Indices of all jobs, sorted so every job comes after its dependencies
+pub fn start_order(jobs: &[Job]) -> Result<Vec<usize>, DependencyError> {
+    fn visit(
+        jobs: &[Job],
+        idx: usize,
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+        order: &mut Vec<usize>,
+    ) -> Result<(), DependencyError> {
+        match marks[idx] {
+            Mark::Done => return Ok(()),
+            Mark::Visiting => {
+                let start = path.iter().position(|i| *i == idx).unwrap_or(0);
+                let cycle = path[start..]
+                    .iter()
+                    .chain([&idx])
+                    .map(|i| jobs[*i].title.clone())
+                    .collect();
+
+                return Err(DependencyError::Cycle(cycle));
+            }
+            Mark::Unvisited => {}
+        }
+
+        marks[idx] = Mark::Visiting;
+        path.push(idx);
+
+        for dependency in dependencies(jobs, idx)? {
+            visit(jobs, dependency, marks, path, order)?;
+        }
+
+        path.pop();
+        marks[idx] = Mark::Done;
+        order.push(idx);
+
+        Ok(())
+    }
+
+    let mut marks = vec![Mark::Unvisited; jobs.len()];
+    let mut order = Vec::with_capacity(jobs.len());
+
+    for idx in 0..jobs.len() {
+        visit(jobs, idx, &mut marks, &mut Vec::new(), &mut order)?;
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Jobs titled by their name, depending on the listed titles
+    fn jobs(specs: &[(&str, &[&str])]) -> Vec<Job> {
+        specs
+            .iter()
+            .map(|&(title, depends_on)| {
+                let mut job = Job::new(&"true");
+                job.title = title.to_owned();
+                job.depends_on = depends_on.iter().map(|&dep| dep.to_owned()).collect();
+                job
+            })
+            .collect()
+    }
+
+    fn titles(jobs: &[Job], order: &[usize]) -> Vec<String> {
+        order.iter().map(|&idx| jobs[idx].title.clone()).collect()
+    }
+
+    #[test]
+    fn chain_starts_from_the_end() {
+        let jobs = jobs(&[("web", &["api"]), ("api", &["db"]), ("db", &[])]);
+
+        let order = start_order(&jobs).unwrap();
+        assert_eq!(titles(&jobs, &order), ["db", "api", "web"]);
+    }
+
+    #[test]
+    fn diamond_starts_shared_dependency_once() {
+        let jobs = jobs(&[
+            ("web", &["api", "assets"]),
+            ("api", &["db"]),
+            ("assets", &["db"]),
+            ("db", &[]),
+        ]);
+
+        let order = start_order(&jobs).unwrap();
+        assert_eq!(titles(&jobs, &order), ["db", "api", "assets", "web"]);
+    }
+
+    #[test]
+    fn dependencies_by_id() {
+        let mut jobs = jobs(&[("web", &[]), ("db", &[])]);
+        jobs[0].depends_on = vec![jobs[1].id.to_string()];
+
+        assert_eq!(dependencies(&jobs, 0).unwrap(), [1]);
+    }
+
+    #[test]
+    fn self_dependency_is_a_cycle() {
+        let jobs = jobs(&[("web", &["web"])]);
+
+        let Err(DependencyError::Cycle(cycle)) = start_order(&jobs) else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(cycle, ["web", "web"]);
+    }
+
+    #[test]
+    fn cycle_error_names_the_cycle() {
+        let jobs = jobs(&[
+            ("web", &["api"]),
+            ("api", &["queue"]),
+            ("queue", &["worker"]),
+            ("worker", &["api"]),
+        ]);
+
+        let err = start_order(&jobs).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Dependency cycle: api → queue → worker → api"
+        );
+    }
+
+    #[test]
+    fn unknown_dependency() {
+        let jobs = jobs(&[("web", &["db"])]);
+
+        let Err(DependencyError::Unknown { job, dependency }) = start_order(&jobs) else {
+            panic!("expected an unknown dependency");
+        };
+        assert_eq!((job.as_str(), dependency.as_str()), ("web", "db"));
+    }
+}
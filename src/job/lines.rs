@@ -0,0 +1,245 @@
+//! Access to every line of a parser, scrollback included.
+//!
+//! vt100 only exposes the visible rows, so the scrollback is walked a screen
+//! at a time. Lines are indexed from the oldest one kept in scrollback. Once
+//! the scrollback is full, every new line shifts these indexes: [`process`]
+//! tells by how much, so the output pump can hand out indexes counted from
+//! the start of the output instead.
+
+use std::ops::Range;
+
+use vt100::{Parser, Screen};
+
+/// Lines kept above the screen
+pub fn history_len(parser: &mut Parser) -> usize {
+    let offset = parser.screen().scrollback();
+
+    parser.set_scrollback(usize::MAX);
+    let len = parser.screen().scrollback();
+    parser.set_scrollback(offset);
+
+    len
+}
+
+/// Feed `bytes` to the parser, returns how many lines were dropped from the
+/// front of the scrollback, which holds at most `capacity` lines.
+pub fn process(parser: &mut Parser, bytes: &[u8], capacity: usize) -> usize {
+    // Nothing is kept to point at
+    if capacity == 0 {
+        parser.process(bytes);
+        return 0;
+    }
+
+    // A byte pushes at most one line, except explicit scrolls, so chunks this
+    // small can be counted
+    let chunk = capacity.saturating_sub(2).max(1);
+
+    bytes
+        .chunks(chunk)
+        .map(|bytes| process_chunk(parser, bytes, capacity))
+        .sum()
+}
+
+/// vt100 doesn't report dropped lines, but moves a scrolled back view one line
+/// back for every line pushed into the scrollback. The view is scrolled back
+/// by one line while processing to count them, when there are too many to
+/// count every line kept before is reported as dropped.
+fn process_chunk(parser: &mut Parser, bytes: &[u8], capacity: usize) -> usize {
+    let offset = parser.screen().scrollback();
+    let before = history_len(parser);
+    let rows = usize::from(parser.screen().size().0);
+
+    parser.set_scrollback(1);
+    parser.process(bytes);
+
+    let pinned = parser.screen().scrollback();
+    let pushed = pinned.saturating_sub(1);
+    let after = history_len(parser);
+
+    // Keep a scrolled back view on the same lines, as vt100 does
+    parser.set_scrollback(if offset == 0 {
+        0
+    } else {
+        (offset + pushed).min(after)
+    });
+
+    if after < capacity {
+        0
+    } else if before > 0 && pinned < capacity {
+        (before + pushed).saturating_sub(after)
+    } else {
+        before + rows
+    }
+}
+
+/// Index of the first visible line
+pub fn top(parser: &mut Parser) -> usize {
+    history_len(parser) - parser.screen().scrollback()
+}
+
+/// Scroll the least needed to make `line` visible
+pub fn reveal(parser: &mut Parser, line: usize) {
+    let history = history_len(parser);
+    let rows = usize::from(parser.screen().size().0);
+    let top = history - parser.screen().scrollback();
+
+    let top = if line < top {
+        line
+    } else if line >= top + rows {
+        line + 1 - rows
+    } else {
+        return;
+    };
+
+    parser.set_scrollback(history.saturating_sub(top));
+}
+
+/// Call `f` with the screen and row of each line in `lines`
+pub fn for_each(parser: &mut Parser, lines: Range<usize>, mut f: impl FnMut(usize, &Screen, u16)) {
+    let offset = parser.screen().scrollback();
+    let history = history_len(parser);
+    let rows = usize::from(parser.screen().size().0);
+    let end = lines.end.min(history + rows);
+
+    let mut line = lines.start;
+
+    while line < end {
+        let window = history.saturating_sub(line);
+        parser.set_scrollback(window);
+        let top = history - window;
+
+        for row in (line - top)..rows.min(end - top) {
+            f(top + row, parser.screen(), row as u16);
+        }
+
+        line = top + rows;
+    }
+
+    parser.set_scrollback(offset);
+}
+
+/// Text of a row, without trailing blanks, and the column of each of its
+/// bytes plus the end one
+pub fn row_text(screen: &Screen, row: u16) -> (String, Vec<u16>) {
+    let cols = screen.size().1;
+
+    let mut text = String::new();
+    let mut columns = Vec::new();
+
+    for col in 0..cols {
+        let Some(cell) = screen.cell(row, col) else {
+            break;
+        };
+
+        if cell.is_wide_continuation() {
+            continue;
+        }
+
+        let contents = if cell.has_contents() {
+            cell.contents()
+        } else {
+            " ".to_owned()
+        };
+
+        columns.extend(std::iter::repeat_n(col, contents.len()));
+        text.push_str(&contents);
+    }
+
+    columns.push(cols);
+    text.truncate(text.trim_end_matches(' ').len());
+
+    (text, columns)
+}
+
+/// Text between two positions, both included. Rows are joined with newlines
+/// unless they were soft wrapped.
+pub fn text(parser: &mut Parser, from: (usize, u16), to: (usize, u16)) -> String {
+    let mut out = String::new();
+
+    for_each(parser, from.0..to.0 + 1, |line, screen, row| {
+        let (text, columns) = row_text(screen, row);
+
+        let start_col = if line == from.0 { from.1 } else { 0 };
+        let end_col = if line == to.0 { to.1 } else { u16::MAX };
+
+        out.extend(
+            text.char_indices()
+                .filter(|(idx, _)| (start_col..=end_col).contains(&columns[*idx]))
+                .map(|(_, c)| c),
+        );
+
+        if line != to.0 && !screen.row_wrapped(row) {
+            out.push('\n');
+        }
+    });
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROWS: u16 = 3;
+    const CAPACITY: usize = 5;
+
+    /// Lines `from..to`, each one holding its own index
+    fn numbered(from: usize, to: usize) -> Vec<u8> {
+        (from..to)
+            .flat_map(|line| format!("{line}\r\n").into_bytes())
+            .collect()
+    }
+
+    fn first_kept(parser: &mut Parser) -> String {
+        let mut first = String::new();
+
+        for_each(parser, 0..1, |_, screen, row| {
+            first = row_text(screen, row).0
+        });
+
+        first
+    }
+
+    #[test]
+    fn counts_dropped_lines() {
+        let mut parser = Parser::new(ROWS, 10, CAPACITY);
+        let mut dropped = 0;
+        let mut next = 0;
+
+        for batch in [1, 2, 3, 1, 4, 2, 5] {
+            dropped += process(&mut parser, &numbered(next, next + batch), CAPACITY);
+            next += batch;
+
+            assert_eq!(first_kept(&mut parser), dropped.to_string());
+        }
+    }
+
+    #[test]
+    fn uncountable_chunk_drops_everything_kept_before() {
+        let mut parser = Parser::new(ROWS, 10, CAPACITY);
+
+        process(&mut parser, &numbered(0, 4), CAPACITY);
+        let dropped = process_chunk(&mut parser, &numbered(4, 20), CAPACITY);
+
+        // 2 lines were in the scrollback and 3 on the screen
+        assert_eq!(dropped, 2 + usize::from(ROWS));
+        assert!(first_kept(&mut parser).parse::<usize>().unwrap() >= dropped);
+    }
+
+    #[test]
+    fn scrolled_back_view_stays_on_its_lines() {
+        let mut parser = Parser::new(ROWS, 10, 100);
+
+        process(&mut parser, &numbered(0, 10), 100);
+        parser.set_scrollback(2);
+        process(&mut parser, &numbered(10, 13), 100);
+
+        assert_eq!(parser.screen().scrollback(), 5);
+
+        process(&mut parser, &numbered(13, 14), 100);
+        parser.set_scrollback(0);
+        process(&mut parser, &numbered(14, 15), 100);
+
+        assert_eq!(parser.screen().scrollback(), 0);
+    }
+}
@@ -0,0 +1,194 @@
+//! Combined log of every job, foreman-style.
+//!
+//! Each output pump taps the raw stream of its job into lines, stripped of
+//! escape sequences, and appends them to [`LOG`] in arrival order.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+
+use tokio::sync::Notify;
+
+/// Lines kept in the combined log, older ones are dropped
+pub const MAX_LINES: usize = 10_000;
+
+/// Longer lines are split, a job printing without newlines can't grow a line
+/// without bounds
+const MAX_LINE_LEN: usize = 4096;
+
+const TAB_WIDTH: usize = 8;
+
+pub static LOG: CombinedLog = CombinedLog::new();
+
+pub struct LogLine {
+    /// Arrival order, unique during the session
+    pub seq: u64,
+    /// [`super::Job::id`] of the job that printed the line
+    pub job: u64,
+    pub text: String,
+}
+
+struct Lines {
+    lines: VecDeque<LogLine>,
+    next_seq: u64,
+}
+
+pub struct CombinedLog {
+    lines: Mutex<Lines>,
+    /// Notified when lines are appended
+    pub notify: Notify,
+}
+
+impl CombinedLog {
+    const fn new() -> Self {
+        Self {
+            lines: Mutex::new(Lines {
+                lines: VecDeque::new(),
+                next_seq: 0,
+            }),
+            notify: Notify::const_new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Lines> {
+        // Lines are plain data, a panic while holding the lock can't leave
+        // them inconsistent
+        self.lines.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn append(&self, job: u64, texts: Vec<String>) {
+        if texts.is_empty() {
+            return;
+        }
+
+        {
+            let mut log = self.lock();
+
+            for text in texts {
+                let seq = log.next_seq;
+                log.next_seq += 1;
+                log.lines.push_back(LogLine { seq, job, text });
+            }
+
+            let excess = log.lines.len().saturating_sub(MAX_LINES);
+            log.lines.drain(..excess);
+        }
+
+        self.notify.notify_one();
+    }
+
+    /// Run `f` over the lines, oldest first
+    pub fn with_lines<R>(&self, f: impl FnOnce(&VecDeque<LogLine>) -> R) -> R {
+        f(&self.lock().lines)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Escape {
+    Ground,
+    /// After `ESC`
+    Esc,
+    /// `ESC (` and friends, one more byte to skip
+    EscArg,
+    /// Control sequence, until a final byte
+    Csi,
+    /// OSC, DCS... until `BEL` or `ESC \`
+    String,
+    /// `ESC` inside a string
+    StringEsc,
+}
+
+/// Splits the raw output of a job into lines for [`LOG`]
+pub struct LineTap {
+    job: u64,
+    line: Vec<u8>,
+    escape: Escape,
+    /// A carriage return was seen, the next printable byte starts the line
+    /// over (progress bars)
+    carriage_return: bool,
+}
+
+impl LineTap {
+    pub const fn new(job: u64) -> Self {
+        Self {
+            job,
+            line: Vec::new(),
+            escape: Escape::Ground,
+            carriage_return: false,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut lines = Vec::new();
+
+        for &byte in bytes {
+            self.escape = match (self.escape, byte) {
+                (Escape::Ground, 0x1b) => Escape::Esc,
+                (Escape::Ground, b'\n') => {
+                    lines.push(self.take_line());
+                    Escape::Ground
+                }
+                (Escape::Ground, b'\r') => {
+                    self.carriage_return = true;
+                    Escape::Ground
+                }
+                (Escape::Ground, b'\t') => {
+                    self.start_char();
+                    self.line.push(b' ');
+                    while !self.line.len().is_multiple_of(TAB_WIDTH) {
+                        self.line.push(b' ');
+                    }
+                    Escape::Ground
+                }
+                // Other control characters
+                (Escape::Ground, ..0x20 | 0x7f) => Escape::Ground,
+                (Escape::Ground, _) => {
+                    self.start_char();
+                    self.line.push(byte);
+
+                    if self.line.len() >= MAX_LINE_LEN {
+                        lines.push(self.take_line());
+                    }
+
+                    Escape::Ground
+                }
+
+                (Escape::Esc, b'[') => Escape::Csi,
+                (Escape::Esc, b']' | b'P' | b'X' | b'^' | b'_') => Escape::String,
+                (Escape::Esc, b' '..=b'/') => Escape::EscArg,
+                (Escape::Esc | Escape::EscArg, _) => Escape::Ground,
+
+                (Escape::Csi, 0x40..=0x7e) => Escape::Ground,
+                (Escape::Csi, _) => Escape::Csi,
+
+                (Escape::String, 0x07) => Escape::Ground,
+                (Escape::String, 0x1b) => Escape::StringEsc,
+                (Escape::StringEsc, b'\\') => Escape::Ground,
+                (Escape::String | Escape::StringEsc, _) => Escape::String,
+            };
+        }
+
+        LOG.append(self.job, lines);
+    }
+
+    /// Append what's left of the last line, once the output ended
+    pub fn finish(&mut self) {
+        if !self.line.is_empty() {
+            let line = self.take_line();
+            LOG.append(self.job, vec![line]);
+        }
+    }
+
+    fn start_char(&mut self) {
+        if self.carriage_return {
+            self.carriage_return = false;
+            self.line.clear();
+        }
+    }
+
+    fn take_line(&mut self) -> String {
+        self.carriage_return = false;
+        let line = String::from_utf8_lossy(&self.line).into_owned();
+        self.line.clear();
+        line
+    }
+}
@@ -0,0 +1,213 @@
+//! Pumping the output of a job's PTY into its terminal emulator.
+//!
+//! The PTY master is read with blocking calls, so each job gets a dedicated
+//! reader thread instead of pinning a tokio worker. Chunks go through a
+//! bounded channel: when the UI side falls behind, the reader thread blocks,
+//! the kernel buffer fills up and the job itself blocks on write, so a job
+//! flooding output can't grow memory without bounds.
+//!
+//! The vt100 parser is owned by the pump task, which publishes a
+//! [`Snapshot`] of the screen after every batch. The UI only loads the latest
+//! snapshot, without locks, and talks to the parser through [`Control`].
+//!
+//! Lines exchanged with the UI are counted from the start of the output, so
+//! they keep pointing at the same line once the scrollback is full (see
+//! [`lines::process`]).
+
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use arc_swap::ArcSwap;
+use regex::Regex;
+use tokio::sync::{Notify, mpsc, oneshot};
+use vt100::Parser;
+
+use super::lines;
+use super::log::LineTap;
+use super::ready::{self, OutputMatcher};
+use super::search::Search;
+use crate::vterm::Snapshot;
+
+/// Size of a single read from the PTY
+const READ_SIZE: usize = 16 * 1024;
+
+/// Chunks buffered between the reader thread and the parser
+const CHANNEL_CHUNKS: usize = 16;
+
+/// Max bytes parsed between two published snapshots, so the screen keeps
+/// updating while a job floods its output
+const MAX_BATCH: usize = 256 * 1024;
+
+/// Requests from the UI to the parser
+pub enum Control {
+    Resize {
+        rows: u16,
+        cols: u16,
+    },
+    /// Move the view into the scrollback, positive goes back in time
+    Scroll(isize),
+    /// Show the bottom of the screen again, following new output
+    Follow,
+    /// Start a new search, or clear it with `None`
+    Search(Option<Regex>),
+    /// Select the next search match, `true` goes towards newer output
+    SearchStep(bool),
+    /// Scroll the least needed to make a line visible
+    Reveal(usize),
+    /// Text between two `(line, col)` positions, both included. Lines dropped
+    /// from the scrollback are skipped
+    Text {
+        from: (usize, u16),
+        to: (usize, u16),
+        reply: oneshot::Sender<String>,
+    },
+}
+
+/// Where the output of a job ends up
+pub struct OutputSink {
+    pub parser: Parser,
+    /// Lines the scrollback of [`OutputSink::parser`] holds
+    pub scrollback: usize,
+    pub screen: Arc<ArcSwap<Snapshot>>,
+    pub matcher: Option<OutputMatcher>,
+    pub ready: Arc<AtomicBool>,
+    pub notify: Arc<Notify>,
+}
+
+/// Start reading `reader` until EOF, feeding everything into `sink`
+pub fn spawn(
+    id: u64,
+    mut reader: Box<dyn Read + Send>,
+    sink: OutputSink,
+    control: mpsc::UnboundedReceiver<Control>,
+) -> io::Result<()> {
+    let (tx, rx) = mpsc::channel(CHANNEL_CHUNKS);
+
+    std::thread::Builder::new()
+        .name(format!("buj-pty-{id}"))
+        .spawn(move || {
+            let mut buf = vec![0; READ_SIZE];
+
+            loop {
+                let size = match reader.read(&mut buf) {
+                    Ok(0) => return,
+                    Ok(size) => size,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    // EIO once every slave fd is closed
+                    Err(_) => return,
+                };
+
+                if tx.blocking_send(buf[..size].to_vec()).is_err() {
+                    return;
+                }
+            }
+        })?;
+
+    tokio::spawn(pump(rx, control, sink, LineTap::new(id)));
+
+    Ok(())
+}
+
+async fn pump(
+    mut rx: mpsc::Receiver<Vec<u8>>,
+    mut control: mpsc::UnboundedReceiver<Control>,
+    mut sink: OutputSink,
+    mut tap: LineTap,
+) {
+    let mut search: Option<Search> = None;
+    let mut eof = false;
+    // Lines dropped from the front of the scrollback so far
+    let mut dropped = 0;
+
+    loop {
+        tokio::select! {
+            // Keeps running after EOF, the output can still be scrolled
+            control = control.recv() => match control {
+                // The job was dropped
+                None => return,
+                Some(Control::Resize { rows, cols }) => {
+                    sink.parser.set_size(rows, cols);
+
+                    if let Some(ref mut search) = search {
+                        search.invalidate();
+                    }
+                }
+                Some(Control::Scroll(delta)) => {
+                    let offset = sink.parser.screen().scrollback().saturating_add_signed(delta);
+                    sink.parser.set_scrollback(offset);
+                }
+                Some(Control::Follow) => sink.parser.set_scrollback(0),
+                Some(Control::Search(regex)) => {
+                    search = regex.map(|regex| Search::new(regex, &mut sink.parser, dropped));
+                }
+                Some(Control::SearchStep(forward)) => {
+                    if let Some(ref mut search) = search {
+                        search.step(&mut sink.parser, forward, dropped);
+                    }
+                }
+                Some(Control::Reveal(line)) => {
+                    lines::reveal(&mut sink.parser, line.saturating_sub(dropped));
+                }
+                Some(Control::Text { from, to, reply }) => {
+                    let text = if to.0 < dropped {
+                        String::new()
+                    } else {
+                        let from = if from.0 < dropped { (dropped, 0) } else { from };
+                        lines::text(
+                            &mut sink.parser,
+                            (from.0 - dropped, from.1),
+                            (to.0 - dropped, to.1),
+                        )
+                    };
+
+                    _ = reply.send(text);
+                    continue;
+                }
+            },
+            batch = rx.recv(), if !eof => {
+                let Some(mut batch) = batch else {
+                    eof = true;
+                    tap.finish();
+                    continue;
+                };
+
+                // Coalesce whatever is already queued
+                while batch.len() < MAX_BATCH
+                    && let Ok(chunk) = rx.try_recv()
+                {
+                    batch.extend_from_slice(&chunk);
+                }
+
+                dropped += lines::process(&mut sink.parser, &batch, sink.scrollback);
+                tap.feed(&batch);
+
+                if let Some(ref mut search) = search {
+                    search.invalidate();
+                }
+
+                if let Some(ref mut matcher) = sink.matcher
+                    && matcher.feed(&batch, sink.parser.screen())
+                {
+                    ready::mark_ready(&sink.ready);
+                    sink.matcher = None;
+                }
+            }
+        }
+
+        let mut snapshot = Snapshot::new(sink.parser.screen());
+        snapshot.history = lines::history_len(&mut sink.parser);
+        snapshot.dropped = dropped;
+        snapshot.top = dropped + snapshot.history - snapshot.scrollback;
+
+        if let Some(ref search) = search {
+            snapshot.highlights = search.highlights(&mut sink.parser, dropped);
+            snapshot.search = Some(search.info());
+        }
+
+        sink.screen.store(Arc::new(snapshot));
+        sink.notify.notify_one();
+
+        tokio::task::yield_now().await;
+    }
+}
@@ -0,0 +1,129 @@
+//! Signal delivery to the whole process tree of a job.
+//!
+//! Jobs are spawned as session leaders (portable-pty calls `setsid` before
+//! `exec`), so the job pid is also its process group and session id. Stopping
+//! only the leader leaves behind grandchildren like the node server spawned by
+//! `npm run dev`, so the group is signaled and `/proc` is swept for processes
+//! that escaped it.
+
+use std::collections::HashSet;
+use std::fs;
+
+use rustix::process::{Pid, Signal};
+
+/// Entry of `/proc/<pid>/stat`
+struct ProcStat {
+    pid: i32,
+    ppid: i32,
+    pgrp: i32,
+    session: i32,
+}
+
+impl ProcStat {
+    fn read(pid: i32) -> Option<Self> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+
+        // `comm` is wrapped in parentheses and can contain spaces or parentheses
+        let (_, fields) = stat.rsplit_once(')')?;
+        let mut fields = fields.split_whitespace().skip(1);
+
+        Some(Self {
+            pid,
+            ppid: fields.next()?.parse().ok()?,
+            pgrp: fields.next()?.parse().ok()?,
+            session: fields.next()?.parse().ok()?,
+        })
+    }
+
+    fn all() -> Vec<Self> {
+        let Ok(dir) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+
+        dir.filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .filter_map(Self::read)
+            .collect()
+    }
+}
+
+/// Processes that belong to the job but are outside of its process group:
+/// members of its session on other groups (pipelines of a job-control shell)
+/// and descendants that moved to their own session.
+fn escaped(leader: i32) -> Vec<i32> {
+    let procs = ProcStat::all();
+
+    let mut tree = HashSet::from([leader]);
+    let mut found = true;
+
+    // Walk down the parent links until no new descendant is found
+    while found {
+        found = false;
+
+        for proc in &procs {
+            if tree.contains(&proc.ppid) && tree.insert(proc.pid) {
+                found = true;
+            }
+        }
+    }
+
+    procs
+        .iter()
+        .filter(|proc| proc.pgrp != leader)
+        .filter(|proc| proc.session == leader || tree.contains(&proc.pid))
+        .map(|proc| proc.pid)
+        .collect()
+}
+
+/// Send `signal` to the process group of `leader` and every process of the job
+/// that escaped it.
+///
+/// Returns whether any process was signaled.
+pub fn signal_tree(leader: u32, signal: Signal) -> bool {
+    let Some(leader) = i32::try_from(leader).ok().and_then(Pid::from_raw) else {
+        return false;
+    };
+
+    // Collect before signaling, once the leader dies its children are
+    // reparented and can only be found by session
+    let escaped = escaped(leader.as_raw_nonzero().get());
+
+    let mut signaled = rustix::process::kill_process_group(leader, signal).is_ok();
+
+    for pid in escaped.into_iter().filter_map(Pid::from_raw) {
+        signaled |= rustix::process::kill_process(pid, signal).is_ok();
+    }
+
+    signaled
+}
+
+/// Processes of a job whose leader was already reaped: the members left in its
+/// group or session.
+///
+/// The leader pid may belong to an unrelated process by now, so its group
+/// isn't signaled and its parent links aren't followed. The kernel doesn't
+/// reuse a pid while it's still a group or session id, these processes can
+/// only be part of the job.
+fn leftovers(leader: i32) -> Vec<i32> {
+    ProcStat::all()
+        .iter()
+        .filter(|proc| proc.pgrp == leader || proc.session == leader)
+        .map(|proc| proc.pid)
+        .collect()
+}
+
+/// Send `signal` to the processes a reaped `leader` left behind, one by one.
+///
+/// Returns whether any process was signaled.
+pub fn signal_leftovers(leader: u32, signal: Signal) -> bool {
+    let Ok(leader) = i32::try_from(leader) else {
+        return false;
+    };
+
+    let mut signaled = false;
+
+    for pid in leftovers(leader).into_iter().filter_map(Pid::from_raw) {
+        signaled |= rustix::process::kill_process(pid, signal).is_ok();
+    }
+
+    signaled
+}
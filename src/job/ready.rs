@@ -0,0 +1,135 @@
+//! Readiness probes: a job is "starting" until its probe succeeds.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use super::{JobExit, LIFECYCLE};
+use regex::Regex;
+use thiserror::Error;
+
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Longest output kept to match lines split across reads
+const MAX_TAIL_LEN: usize = 4096;
+
+#[derive(Clone, Debug)]
+pub enum ReadyCheck {
+    /// A regex matches the job output
+    Output(Regex),
+    /// A local TCP port accepts connections
+    Port(u16),
+    /// A path exists
+    Path(PathBuf),
+}
+
+#[derive(Debug, Error)]
+pub enum ReadyCheckParseError {
+    #[error("Unknown readiness check `{0}`, expected output:<regex>, port:<port> or path:<path>")]
+    Unknown(String),
+    #[error("Invalid port: {0}")]
+    Port(#[from] std::num::ParseIntError),
+    #[error("Invalid regex: {0}")]
+    Regex(#[from] regex::Error),
+}
+
+impl FromStr for ReadyCheck {
+    type Err = ReadyCheckParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("output", regex)) => Ok(Self::Output(Regex::new(regex)?)),
+            Some(("port", port)) => Ok(Self::Port(port.trim().parse()?)),
+            Some(("path", path)) => Ok(Self::Path(PathBuf::from(path))),
+            _ => Err(ReadyCheckParseError::Unknown(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for ReadyCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Output(regex) => write!(f, "output:{regex}"),
+            Self::Port(port) => write!(f, "port:{port}"),
+            Self::Path(path) => write!(f, "path:{}", path.display()),
+        }
+    }
+}
+
+pub fn mark_ready(ready: &AtomicBool) {
+    if !ready.swap(true, Ordering::Relaxed) {
+        LIFECYCLE.notify_one();
+    }
+}
+
+/// Matches [`ReadyCheck::Output`] against the raw stream and the rendered
+/// screen, the latter catches text split by escape sequences (colors...).
+pub struct OutputMatcher {
+    regex: Regex,
+    /// Last incomplete line of the stream
+    tail: String,
+}
+
+impl OutputMatcher {
+    pub fn new(check: Option<&ReadyCheck>) -> Option<Self> {
+        let Some(ReadyCheck::Output(regex)) = check else {
+            return None;
+        };
+
+        Some(Self {
+            regex: regex.clone(),
+            tail: String::new(),
+        })
+    }
+
+    pub fn feed(&mut self, chunk: &[u8], screen: &vt100::Screen) -> bool {
+        self.tail.push_str(&String::from_utf8_lossy(chunk));
+
+        if self.regex.is_match(&self.tail) || self.regex.is_match(&screen.contents()) {
+            return true;
+        }
+
+        if let Some(idx) = self.tail.rfind('\n') {
+            self.tail.drain(..=idx);
+        }
+
+        if self.tail.len() > MAX_TAIL_LEN {
+            let mut start = self.tail.len() - MAX_TAIL_LEN;
+
+            while !self.tail.is_char_boundary(start) {
+                start += 1;
+            }
+
+            self.tail.drain(..start);
+        }
+
+        false
+    }
+}
+
+/// Poll port and path checks until they succeed or the job exits
+pub fn spawn_probe(check: &ReadyCheck, ready: Arc<AtomicBool>, exit: Arc<OnceLock<JobExit>>) {
+    let check = check.clone();
+
+    tokio::spawn(async move {
+        while exit.get().is_none() {
+            let is_ready = match check {
+                ReadyCheck::Output(_) => return,
+                ReadyCheck::Port(port) => tokio::net::TcpStream::connect(("127.0.0.1", port))
+                    .await
+                    .is_ok(),
+                ReadyCheck::Path(ref path) => tokio::fs::try_exists(path).await.unwrap_or(false),
+            };
+
+            if is_ready {
+                mark_ready(&ready);
+                return;
+            }
+
+            tokio::time::sleep(PROBE_INTERVAL).await;
+        }
+    });
+}
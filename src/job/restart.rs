@@ -0,0 +1,116 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use super::Exit;
+
+/// When a job is started again after its process exits.
+///
+/// A manual stop (`k`) always wins over the policy while buj is running, the
+/// difference between [`RestartPolicy::Always`] and
+/// [`RestartPolicy::UnlessStopped`] is whether a stopped job comes back when
+/// the session is recovered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+    UnlessStopped,
+}
+
+impl RestartPolicy {
+    pub const ALL: [Self; 4] = [
+        Self::Never,
+        Self::OnFailure,
+        Self::Always,
+        Self::UnlessStopped,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::OnFailure => "on-failure",
+            Self::Always => "always",
+            Self::UnlessStopped => "unless-stopped",
+        }
+    }
+
+    /// Next policy, used to cycle through them from the UI
+    #[must_use]
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|p| *p == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Whether a process that ended with `exit` must be restarted
+    pub fn should_restart(self, exit: Exit, stopped: bool) -> bool {
+        match self {
+            Self::Never => false,
+            _ if stopped => false,
+            Self::OnFailure => !exit.success(),
+            Self::Always | Self::UnlessStopped => true,
+        }
+    }
+}
+
+impl fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Unknown restart policy `{0}`, expected never, on-failure, always or unless-stopped")]
+pub struct UnknownRestartPolicy(String);
+
+impl FromStr for RestartPolicy {
+    type Err = UnknownRestartPolicy;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| UnknownRestartPolicy(s.to_owned()))
+    }
+}
+
+/// Restart policy of a job plus its backoff configuration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Restart {
+    pub policy: RestartPolicy,
+    /// Give up after this many consecutive restarts, `None` retries forever
+    pub max_retries: Option<u32>,
+    /// Delay before the first restart, doubled on each retry
+    pub backoff: Duration,
+    /// Upper bound of the backoff delay, a run that lasts as long resets the
+    /// retries
+    pub max_backoff: Duration,
+}
+
+impl Default for Restart {
+    fn default() -> Self {
+        Self {
+            policy: RestartPolicy::default(),
+            max_retries: Some(5),
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl Restart {
+    /// Delay before the restart number `retry` (starting at 0), or `None` if
+    /// retries are exhausted
+    pub fn delay(&self, retry: u32) -> Option<Duration> {
+        if self.max_retries.is_some_and(|max| retry >= max) {
+            return None;
+        }
+
+        let factor = 1u32.checked_shl(retry).unwrap_or(u32::MAX);
+
+        Some(self.backoff.saturating_mul(factor).min(self.max_backoff))
+    }
+}
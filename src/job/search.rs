@@ -0,0 +1,157 @@
+//! Searching the output of a job, scrollback included.
+//!
+//! Runs on the output pump, which owns the parser. Matches are located by
+//! line index counted from the start of the output, `dropped` being the
+//! number of lines the scrollback already dropped (see [`lines::process`]).
+
+use regex::Regex;
+use vt100::{Parser, Screen};
+
+use super::lines;
+use crate::vterm::{Highlight, SearchInfo};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Match {
+    line: usize,
+    start: u16,
+    end: u16,
+}
+
+pub struct Search {
+    regex: Regex,
+    matches: Vec<Match>,
+    current: Option<usize>,
+    /// The output changed since `matches` was collected
+    stale: bool,
+}
+
+impl Search {
+    /// Search `regex` and select the oldest match
+    pub fn new(regex: Regex, parser: &mut Parser, dropped: usize) -> Self {
+        let mut search = Self {
+            regex,
+            matches: Vec::new(),
+            current: None,
+            stale: true,
+        };
+
+        search.step(parser, true, dropped);
+        search
+    }
+
+    /// Called when new output arrives, matches are collected again on the
+    /// next step
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Select the next match, `forward` goes towards newer output
+    pub fn step(&mut self, parser: &mut Parser, forward: bool, dropped: usize) {
+        let current = self.refresh(parser, dropped);
+        let len = self.matches.len();
+
+        if len == 0 {
+            self.current = None;
+            return;
+        }
+
+        self.current = Some(match current {
+            None if forward => 0,
+            None => len - 1,
+            Some(idx) if forward => (idx + 1) % len,
+            Some(idx) => (idx + len - 1) % len,
+        });
+
+        self.reveal(parser, dropped);
+    }
+
+    /// Collect the matches again if stale, returns the index of the current
+    /// match in the new list
+    fn refresh(&mut self, parser: &mut Parser, dropped: usize) -> Option<usize> {
+        let current = self.current.map(|idx| self.matches[idx]);
+
+        if self.stale {
+            self.matches = find_all(&self.regex, parser, dropped);
+            self.stale = false;
+        }
+
+        let current = current?;
+
+        match self.matches.binary_search(&current) {
+            Ok(idx) => Some(idx),
+            // Gone with the scrollback, continue from the one after it
+            Err(idx) => idx.checked_sub(1),
+        }
+    }
+
+    /// Scroll the current match into view
+    fn reveal(&self, parser: &mut Parser, dropped: usize) {
+        let Some(line) = self.current.map(|idx| self.matches[idx].line - dropped) else {
+            return;
+        };
+
+        let history = lines::history_len(parser);
+        let rows = usize::from(parser.screen().size().0);
+        let top = lines::top(parser);
+
+        if (top..top + rows).contains(&line) {
+            return;
+        }
+
+        let top = line.saturating_sub(rows / 2).min(history);
+        parser.set_scrollback(history - top);
+    }
+
+    /// Matches on the visible rows
+    pub fn highlights(&self, parser: &mut Parser, dropped: usize) -> Vec<Highlight> {
+        let current = self.current.map(|idx| self.matches[idx]);
+        let top = dropped + lines::top(parser);
+        let rows = parser.screen().size().0;
+
+        (0..rows)
+            .flat_map(|row| {
+                let line = top + usize::from(row);
+
+                find_in_row(&self.regex, parser.screen(), row, line)
+                    .into_iter()
+                    .map(move |found| Highlight {
+                        row,
+                        start: found.start,
+                        end: found.end,
+                        current: current == Some(found),
+                    })
+            })
+            .collect()
+    }
+
+    pub fn info(&self) -> SearchInfo {
+        SearchInfo {
+            current: self.current,
+            total: self.matches.len(),
+        }
+    }
+}
+
+fn find_all(regex: &Regex, parser: &mut Parser, dropped: usize) -> Vec<Match> {
+    let mut matches = Vec::new();
+
+    lines::for_each(parser, 0..usize::MAX, |line, screen, row| {
+        matches.extend(find_in_row(regex, screen, row, dropped + line));
+    });
+
+    matches
+}
+
+fn find_in_row(regex: &Regex, screen: &Screen, row: u16, line: usize) -> Vec<Match> {
+    let (text, columns) = lines::row_text(screen, row);
+
+    regex
+        .find_iter(&text)
+        .filter(|found| !found.is_empty())
+        .map(|found| Match {
+            line,
+            start: columns[found.start()],
+            end: columns[found.end()],
+        })
+        .collect()
+}
@@ -0,0 +1,79 @@
+//! Running the command line of a job through a shell.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Used when neither the job nor `$SHELL` tell which shell to use
+const FALLBACK_SHELL: &str = "/bin/sh";
+
+/// Shell used to interpret the command line, so pipes, redirects, `&&`,
+/// globs and variables work as typed
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shell {
+    /// `None` uses `$SHELL`, falling back to `/bin/sh`
+    pub program: Option<PathBuf>,
+    /// Run as a login shell (`-l`), sourcing the user's profile first
+    pub login: bool,
+}
+
+impl Shell {
+    pub fn program(&self) -> PathBuf {
+        self.program
+            .clone()
+            .or_else(|| {
+                std::env::var_os("SHELL")
+                    .filter(|shell| !shell.is_empty())
+                    .map(PathBuf::from)
+            })
+            .unwrap_or_else(|| PathBuf::from(FALLBACK_SHELL))
+    }
+
+    /// Arguments passed to [`Shell::program`] to run `cmd`
+    pub fn args(&self, cmd: &str) -> [OsString; 2] {
+        [self.flags().into(), cmd.into()]
+    }
+
+    fn flags(&self) -> &'static str {
+        if self.login { "-lc" } else { "-c" }
+    }
+}
+
+impl fmt::Display for Shell {
+    /// e.g. `zsh -lc`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let program = self.program();
+        let name = program
+            .file_name()
+            .map_or_else(|| program.as_os_str(), |name| name)
+            .to_string_lossy();
+
+        write!(f, "{name} {}", self.flags())
+    }
+}
+
+/// Short description of how a job with the given shell is executed
+pub fn describe(shell: Option<&Shell>) -> String {
+    match shell {
+        None => "direct".to_owned(),
+        Some(shell) if shell.login => format!("login shell ({shell})"),
+        Some(shell) => format!("shell ({shell})"),
+    }
+}
+
+/// `arg` quoted for a command line when it needs to
+pub fn quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+
+    if plain {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,152 @@
+use ratatui::style::Color;
+use rustix::process::{Signal, WaitIdStatus};
+use tokio::time::Instant;
+
+/// Signals with a known name, used for labels
+const SIGNALS: [(Signal, &str); 15] = [
+    (Signal::HUP, "SIGHUP"),
+    (Signal::INT, "SIGINT"),
+    (Signal::QUIT, "SIGQUIT"),
+    (Signal::ILL, "SIGILL"),
+    (Signal::TRAP, "SIGTRAP"),
+    (Signal::ABORT, "SIGABRT"),
+    (Signal::BUS, "SIGBUS"),
+    (Signal::FPE, "SIGFPE"),
+    (Signal::KILL, "SIGKILL"),
+    (Signal::USR1, "SIGUSR1"),
+    (Signal::SEGV, "SIGSEGV"),
+    (Signal::USR2, "SIGUSR2"),
+    (Signal::PIPE, "SIGPIPE"),
+    (Signal::ALARM, "SIGALRM"),
+    (Signal::TERM, "SIGTERM"),
+];
+
+pub fn signal_name(signal: i32) -> Option<&'static str> {
+    SIGNALS
+        .iter()
+        .find(|(sig, _)| sig.as_raw() == signal)
+        .map(|(_, name)| *name)
+}
+
+/// Parse a signal by name (`SIGTERM`, `term`) or number
+pub fn parse_signal(name: &str) -> Option<Signal> {
+    if let Ok(number) = name.parse::<i32>() {
+        return SIGNALS
+            .iter()
+            .find(|(sig, _)| sig.as_raw() == number)
+            .map(|(sig, _)| *sig);
+    }
+
+    let name = name.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+
+    SIGNALS
+        .iter()
+        .find(|(_, known)| known[3..] == *name)
+        .map(|(sig, _)| *sig)
+}
+
+/// How a process ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    Code(u32),
+    Signal { signal: i32, core_dumped: bool },
+}
+
+impl Exit {
+    pub fn success(self) -> bool {
+        self == Self::Code(0)
+    }
+}
+
+impl From<&WaitIdStatus> for Exit {
+    fn from(status: &WaitIdStatus) -> Self {
+        if let Some(signal) = status.terminating_signal() {
+            Self::Signal {
+                signal,
+                core_dumped: status.dumped(),
+            }
+        } else {
+            Self::Code(status.exit_status().unwrap_or(1).cast_unsigned())
+        }
+    }
+}
+
+/// Exit of a process and when it happened
+#[derive(Clone, Copy, Debug)]
+pub struct JobExit {
+    pub exit: Exit,
+    pub at: Instant,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    NotStarted,
+    Running,
+    /// The stop signal was sent, waiting for it to exit
+    Stopping,
+    Exited(u32),
+    /// Killed by a signal, and whether it dumped core
+    Signaled(i32, bool),
+    /// The process could not be spawned
+    SpawnFailed(String),
+}
+
+impl JobStatus {
+    pub fn color(&self) -> Color {
+        match self {
+            Self::NotStarted => Color::Gray,
+            Self::Running => Color::LightBlue,
+            Self::Stopping => Color::Yellow,
+            Self::Exited(0) => Color::Green,
+            Self::Exited(_) => Color::Red,
+            Self::Signaled(..) => Color::Magenta,
+            Self::SpawnFailed(_) => Color::LightRed,
+        }
+    }
+
+    /// Prefix of the label shown on the footer
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Exited(_) => "Exit code: ",
+            Self::Signaled(..) => "Signal: ",
+            Self::SpawnFailed(_) => "Spawn failed: ",
+            _ => "",
+        }
+    }
+
+    /// Label of the status, see also [`JobStatus::kind`]
+    pub fn label(&self) -> String {
+        match self {
+            Self::Signaled(_, true) => format!("{} (core dumped)", self.short_label()),
+            Self::SpawnFailed(err) => err.clone(),
+            _ => self.short_label(),
+        }
+    }
+
+    /// Short label for narrow places like the sidebar
+    pub fn short_label(&self) -> String {
+        match self {
+            Self::NotStarted => String::new(),
+            Self::Running => "running".to_owned(),
+            Self::Stopping => "stopping…".to_owned(),
+            Self::Exited(code) => code.to_string(),
+            Self::Signaled(signal, _) => {
+                signal_name(*signal).map_or_else(|| format!("signal {signal}"), str::to_owned)
+            }
+            Self::SpawnFailed(_) => "spawn failed".to_owned(),
+        }
+    }
+}
+
+impl From<Exit> for JobStatus {
+    fn from(exit: Exit) -> Self {
+        match exit {
+            Exit::Code(code) => Self::Exited(code),
+            Exit::Signal {
+                signal,
+                core_dumped,
+            } => Self::Signaled(signal, core_dumped),
+        }
+    }
+}
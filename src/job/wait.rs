@@ -0,0 +1,47 @@
+//! Waiting for a job to exit without polling.
+//!
+//! A pidfd becomes readable as soon as the process exits, so each job awaits
+//! its own pidfd on the tokio reactor. When no pidfd can be opened (kernels
+//! before 5.3, no file descriptor left) it falls back to waking up on
+//! `SIGCHLD`, which tokio delivers to every listener.
+
+use std::io;
+
+use rustix::process::{Pid, PidfdFlags, WaitId, WaitIdOptions, WaitIdStatus};
+use tokio::io::Interest;
+use tokio::io::unix::AsyncFd;
+use tokio::signal::unix::{SignalKind, signal};
+
+/// Wait until the child `pid` exits and reap it
+pub async fn wait_exit(pid: Pid) -> io::Result<WaitIdStatus> {
+    match rustix::process::pidfd_open(pid, PidfdFlags::NONBLOCK) {
+        Ok(pidfd) => {
+            let pidfd = AsyncFd::with_interest(pidfd, Interest::READABLE)?;
+            let _ready = pidfd.readable().await?;
+
+            // Readable means exited, this doesn't block
+            try_reap(pid)?.ok_or_else(|| io::Error::other("pidfd ready but child is alive"))
+        }
+        Err(_) => wait_sigchld(pid).await,
+    }
+}
+
+async fn wait_sigchld(pid: Pid) -> io::Result<WaitIdStatus> {
+    // Registered before the first check so an exit in between is not missed
+    let mut sigchld = signal(SignalKind::child())?;
+
+    loop {
+        if let Some(status) = try_reap(pid)? {
+            return Ok(status);
+        }
+
+        sigchld.recv().await;
+    }
+}
+
+fn try_reap(pid: Pid) -> io::Result<Option<WaitIdStatus>> {
+    Ok(rustix::process::waitid(
+        WaitId::Pid(pid),
+        WaitIdOptions::EXITED | WaitIdOptions::NOHANG,
+    )?)
+}
@@ -0,0 +1,171 @@
+//! Encoding of terminal events into the byte sequences an xterm would send
+//! to the program running inside it.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+const ESC: u8 = 0x1b;
+
+/// Terminal modes requested by the running program that change how input
+/// must be encoded.
+#[derive(Clone, Copy, Default)]
+pub struct InputModes {
+    /// DECCKM: arrows are sent as `ESC O x` instead of `ESC [ x`
+    pub application_cursor: bool,
+    /// Wrap pastes with `ESC [200~` and `ESC [201~`
+    pub bracketed_paste: bool,
+}
+
+impl InputModes {
+    pub fn from_screen(screen: &vt100::Screen) -> Self {
+        Self {
+            application_cursor: screen.application_cursor(),
+            bracketed_paste: screen.bracketed_paste(),
+        }
+    }
+}
+
+/// Encode a key event as xterm input.
+///
+/// Returns `None` for events that don't produce any input (key releases,
+/// lone modifiers, media keys, ...).
+pub fn encode_key(key: KeyEvent, modes: InputModes) -> Option<Vec<u8>> {
+    if key.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+    let mut out = Vec::new();
+
+    match key.code {
+        KeyCode::Char(c) => {
+            if alt {
+                out.push(ESC);
+            }
+
+            if ctrl && let Some(byte) = ctrl_byte(c) {
+                out.push(byte);
+            } else {
+                let c = if shift { c.to_ascii_uppercase() } else { c };
+                out.extend_from_slice(c.encode_utf8(&mut [0; char::MAX_LEN_UTF8]).as_bytes());
+            }
+        }
+        KeyCode::Enter => {
+            if alt {
+                out.push(ESC);
+            }
+            out.push(b'\r');
+        }
+        KeyCode::Tab => {
+            if alt {
+                out.push(ESC);
+            }
+            out.push(b'\t');
+        }
+        KeyCode::BackTab => out.extend_from_slice(b"\x1b[Z"),
+        KeyCode::Backspace => {
+            if alt {
+                out.push(ESC);
+            }
+            out.push(if ctrl { 0x08 } else { 0x7f });
+        }
+        KeyCode::Esc => out.push(ESC),
+        KeyCode::Null => out.push(0),
+
+        KeyCode::Up => cursor_key(&mut out, b'A', key.modifiers, modes),
+        KeyCode::Down => cursor_key(&mut out, b'B', key.modifiers, modes),
+        KeyCode::Right => cursor_key(&mut out, b'C', key.modifiers, modes),
+        KeyCode::Left => cursor_key(&mut out, b'D', key.modifiers, modes),
+        KeyCode::Home => cursor_key(&mut out, b'H', key.modifiers, modes),
+        KeyCode::End => cursor_key(&mut out, b'F', key.modifiers, modes),
+
+        KeyCode::Insert => tilde_key(&mut out, 2, key.modifiers),
+        KeyCode::Delete => tilde_key(&mut out, 3, key.modifiers),
+        KeyCode::PageUp => tilde_key(&mut out, 5, key.modifiers),
+        KeyCode::PageDown => tilde_key(&mut out, 6, key.modifiers),
+
+        KeyCode::F(n @ 1..=4) => {
+            let code = b"PQRS"[usize::from(n - 1)];
+
+            match modifier_param(key.modifiers) {
+                Some(m) => out.extend_from_slice(format!("\x1b[1;{m}").as_bytes()),
+                None => out.extend_from_slice(b"\x1bO"),
+            }
+            out.push(code);
+        }
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][usize::from(n - 5)];
+            tilde_key(&mut out, code, key.modifiers);
+        }
+
+        _ => return None,
+    }
+
+    Some(out)
+}
+
+/// Encode pasted text, honoring bracketed paste mode.
+pub fn encode_paste(text: &str, modes: InputModes) -> Vec<u8> {
+    let mut out = Vec::with_capacity(text.len() + 12);
+
+    if modes.bracketed_paste {
+        out.extend_from_slice(b"\x1b[200~");
+        out.extend_from_slice(text.as_bytes());
+        out.extend_from_slice(b"\x1b[201~");
+    } else {
+        // Terminals send newlines in pastes as carriage returns
+        out.extend(text.bytes().map(|b| if b == b'\n' { b'\r' } else { b }));
+    }
+
+    out
+}
+
+fn ctrl_byte(c: char) -> Option<u8> {
+    match c {
+        'a'..='z' => Some(c as u8 - b'a' + 1),
+        'A'..='Z' => Some(c as u8 - b'A' + 1),
+        ' ' | '@' | '2' => Some(0),
+        '[' | '3' => Some(0x1b),
+        '\\' | '4' => Some(0x1c),
+        ']' | '5' => Some(0x1d),
+        '^' | '6' => Some(0x1e),
+        '_' | '-' | '7' => Some(0x1f),
+        '?' | '8' => Some(0x7f),
+        _ => None,
+    }
+}
+
+/// xterm modifier parameter: `1 + shift + 2*alt + 4*ctrl`
+fn modifier_param(modifiers: KeyModifiers) -> Option<u8> {
+    let mut param = 0;
+
+    if modifiers.contains(KeyModifiers::SHIFT) {
+        param |= 1;
+    }
+    if modifiers.contains(KeyModifiers::ALT) {
+        param |= 2;
+    }
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        param |= 4;
+    }
+
+    (param != 0).then_some(param + 1)
+}
+
+fn cursor_key(out: &mut Vec<u8>, code: u8, modifiers: KeyModifiers, modes: InputModes) {
+    match modifier_param(modifiers) {
+        Some(m) => out.extend_from_slice(format!("\x1b[1;{m}").as_bytes()),
+        None if modes.application_cursor => out.extend_from_slice(b"\x1bO"),
+        None => out.extend_from_slice(b"\x1b["),
+    }
+    out.push(code);
+}
+
+fn tilde_key(out: &mut Vec<u8>, code: u8, modifiers: KeyModifiers) {
+    match modifier_param(modifiers) {
+        Some(m) => out.extend_from_slice(format!("\x1b[{code};{m}~").as_bytes()),
+        None => out.extend_from_slice(format!("\x1b[{code}~").as_bytes()),
+    }
+}
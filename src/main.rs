@@ -23,26 +23,108 @@ extern crate tokio;
 
 mod animation;
 mod app;
+mod cli;
+mod clipboard;
+mod config;
+mod discover;
 mod events;
 mod job;
+mod keys;
+mod procfile;
+mod session;
 mod theme;
 mod ui;
 mod variadicts;
 mod vterm;
 
 use std::io;
+use std::process::ExitCode;
+use std::sync::Arc;
 
 use ratatui::DefaultTerminal;
+use ratatui::style::{Style, Stylize};
 
 use crate::app::App;
-use crate::events::{CaptureMouse, TermEvents};
+use crate::events::{CaptureMouse, CapturePaste, TermEvents};
 use crate::ui::Component;
 
+/// Exit status of a usage error, see [`cli::HELP`]
+const USAGE_ERROR: u8 = 2;
+
 #[tokio::main]
-async fn main() -> io::Result<()> {
+async fn main() -> io::Result<ExitCode> {
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!(
+                "buj: {err}\n\n{}\n\nFor more information, try `--help`.",
+                cli::USAGE
+            );
+            return Ok(ExitCode::from(USAGE_ERROR));
+        }
+    };
+
+    if args.help {
+        println!("{}", cli::HELP);
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if args.version {
+        println!("buj {}", env!("CARGO_PKG_VERSION"));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(ref cwd) = args.cwd
+        && let Err(err) = std::env::set_current_dir(cwd)
+    {
+        eprintln!("buj: cannot run from {}: {err}", cwd.display());
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let config_path = match args.file {
+        Some(path) if !path.is_file() => {
+            eprintln!("buj: {} is not a file", path.display());
+            return Ok(ExitCode::FAILURE);
+        }
+        Some(path) => Some(path),
+        None if args.procfile.is_some() => None,
+        None => config::find(),
+    };
+
+    let filter = config::JobFilter {
+        only: args.only,
+        except: args.except,
+    };
+
+    if !filter.is_empty() && config_path.is_none() && args.procfile.is_none() {
+        eprintln!(
+            "buj: `--only` and `--except` select jobs of a job file, no {} was found",
+            config::FILE_NAME
+        );
+        return Ok(ExitCode::from(USAGE_ERROR));
+    }
+
     let mut terminal = ratatui::init();
 
     let mut app = App::new();
+    app.job_filter = filter;
+
+    if let Some(color) = args.theme {
+        let accent = Style::new().fg(color).bold();
+        app.theme = Arc::new(theme::UserTheme::new().accent(accent).into());
+    }
+
+    if args.no_intro {
+        app.skip_intro();
+    }
+
+    if let Some(path) = args.procfile {
+        app.load_procfile(&path);
+    } else if let Some(path) = config_path {
+        app.open_config(path);
+    }
+
+    app.preload(args.commands);
 
     if std::env::var("BUJ_ANIMATION_DEBUG").is_ok() {
         app.anim.debug();
@@ -52,12 +134,28 @@ async fn main() -> io::Result<()> {
 
     let result = run_app(&mut terminal, &mut app).await;
 
+    // Saved before stopping the jobs, to know which ones were running.
+    // Quitting right away from the welcome screen keeps the saved session
+    let saved = if app.jobs.is_empty() {
+        Ok(())
+    } else {
+        session::save(&app.jobs)
+    };
+
+    app.kill_jobs().await;
+
     ratatui::restore();
-    result
+
+    if let Err(err) = saved {
+        eprintln!("buj: {err}");
+    }
+
+    result.map(|()| ExitCode::SUCCESS)
 }
 
 async fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
-    let _ = CaptureMouse::scoped()?;
+    let _mouse = CaptureMouse::scoped()?;
+    let _paste = CapturePaste::scoped()?;
 
     let mut quitting = false;
 
@@ -81,8 +179,10 @@ async fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()
             app::PopupsState::update(app);
 
             app.update_sidebar();
+            app.update_jobs();
 
             let job_tick = app.job_tick();
+            let uptime_tick = app.uptime_tick();
             let anim = app.anim.wait_tick();
             let sidebar_anim = app.sidebar_anim.wait_tick();
             let popup_anim = app.popup.anim.wait_tick();
@@ -90,6 +190,12 @@ async fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()
             let mut action = tokio::select! {
                 Ok(ev) = TermEvents => App::handle_event(app, ev).await,
                 true = job_tick => ui::Action::Tick,
+                true = uptime_tick => ui::Action::Tick,
+                () = job::LIFECYCLE.notified() => ui::Action::Tick,
+                () = config::watch::CHANGED.notified() => {
+                    app.reload_config();
+                    ui::Action::Tick
+                }
                 true = anim => ui::Action::Noop,
                 true = popup_anim => ui::Action::Noop,
                 true = sidebar_anim => ui::Action::Noop,
@@ -113,8 +219,6 @@ async fn run_app(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()
         }
     }
 
-    app.kill_jobs();
-
     Ok(())
 }
 
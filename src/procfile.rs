@@ -0,0 +1,334 @@
+//! Importing a foreman `Procfile`, one job per process.
+//!
+//! ```text
+//! # Procfile.dev
+//! web: bundle exec rails server -p $PORT
+//! worker: bundle exec sidekiq
+//! ```
+//!
+//! Like foreman, the `.env` file next to the Procfile is loaded into every
+//! process, commands run through `sh` from the directory of the Procfile, and
+//! each process gets a `PORT`: the one of `.env` or 5000 for the first one,
+//! 100 more for each next one.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+use crate::job::Job;
+use crate::job::shell::Shell;
+
+/// Loaded next to the Procfile when present
+pub const ENV_FILE: &str = ".env";
+
+const BASE_PORT: u16 = 5000;
+const PORT_STEP: u16 = 100;
+
+#[derive(Debug, Error)]
+pub enum ProcfileError {
+    #[error("Cannot read {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("{}:{line}: {message}", path.display())]
+    Syntax {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("{}: no process declared", path.display())]
+    Empty { path: PathBuf },
+    #[error("{}: invalid `PORT` `{value}`, expected a port with room for every process", path.display())]
+    InvalidPort { path: PathBuf, value: String },
+}
+
+/// Jobs of the processes of a Procfile, with the variables of its `.env`
+pub fn load(path: &Path) -> Result<Vec<Job>, ProcfileError> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+
+    let env_path = dir.join(ENV_FILE);
+    let env = match std::fs::read_to_string(&env_path) {
+        Ok(text) => parse_env(&text).map_err(|(line, message)| ProcfileError::Syntax {
+            path: env_path,
+            line,
+            message,
+        })?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(source) => {
+            return Err(ProcfileError::Io {
+                path: env_path,
+                source,
+            });
+        }
+    };
+
+    let text = std::fs::read_to_string(path).map_err(|source| ProcfileError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    let processes = parse(&text).map_err(|(line, message)| ProcfileError::Syntax {
+        path: path.to_owned(),
+        line,
+        message,
+    })?;
+
+    if processes.is_empty() {
+        return Err(ProcfileError::Empty {
+            path: path.to_owned(),
+        });
+    }
+
+    let env_port = env
+        .iter()
+        .find(|(key, _)| key == "PORT")
+        .map(|(_, value)| value.as_str());
+
+    let Some(ports) = ports(env_port, processes.len()) else {
+        return Err(ProcfileError::InvalidPort {
+            path: dir.join(ENV_FILE),
+            value: env_port.unwrap_or_default().to_owned(),
+        });
+    };
+
+    let jobs = processes
+        .into_iter()
+        .zip(ports)
+        .map(|((name, cmd), port)| {
+            let mut job = Job::new(&cmd);
+            job.title = name;
+            job.cwd = Some(dir.to_owned());
+            job.shell = Some(Shell {
+                program: Some(PathBuf::from("/bin/sh")),
+                login: false,
+            });
+            job.env.clone_from(&env);
+            job.env.retain(|(key, _)| key != "PORT");
+            job.env.push(("PORT".to_owned(), port.to_string()));
+
+            job
+        })
+        .collect();
+
+    Ok(jobs)
+}
+
+/// `PORT` of each of `count` processes, starting from the `PORT` of `.env`.
+///
+/// `None` when `env_port` isn't a port or the last ones would overflow.
+fn ports(env_port: Option<&str>, count: usize) -> Option<Vec<u16>> {
+    let base = env_port.map_or(Some(BASE_PORT), |port| port.parse().ok())?;
+
+    (0..count)
+        .map(|idx| base.checked_add(u16::try_from(idx).ok()?.checked_mul(PORT_STEP)?))
+        .collect()
+}
+
+/// `(name, command)` of each process, errors are `(line, message)`
+fn parse(text: &str) -> Result<Vec<(String, String)>, (usize, String)> {
+    let mut processes: Vec<(String, String)> = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let number = idx + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((name, cmd)) = line.split_once(':') else {
+            return Err((number, "expected `name: command`".to_owned()));
+        };
+
+        let (name, cmd) = (name.trim(), cmd.trim());
+
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err((
+                number,
+                format!(
+                    "invalid process name `{name}`, only letters, digits, `_` and `-` are allowed"
+                ),
+            ));
+        }
+
+        if cmd.is_empty() {
+            return Err((number, format!("process `{name}` has no command")));
+        }
+
+        if cmd.ends_with('\\') {
+            return Err((number, "line continuations are not supported".to_owned()));
+        }
+
+        if processes.iter().any(|(other, _)| other == name) {
+            return Err((number, format!("process `{name}` is declared twice")));
+        }
+
+        processes.push((name.to_owned(), cmd.to_owned()));
+    }
+
+    Ok(processes)
+}
+
+/// Variables of a `.env` file, errors are `(line, message)`.
+///
+/// `KEY=value` lines, single quotes keep the value as is, double quotes
+/// unescape it (see [`unescape`]).
+fn parse_env(text: &str) -> Result<Vec<(String, String)>, (usize, String)> {
+    let mut env: Vec<(String, String)> = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let number = idx + 1;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if trimmed.starts_with("export ") {
+            return Err((
+                number,
+                "`export` is not supported, use `KEY=value`".to_owned(),
+            ));
+        }
+
+        let Some((key, value)) = trimmed.split_once('=') else {
+            return Err((number, "expected `KEY=value`".to_owned()));
+        };
+
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err((
+                number,
+                format!("invalid variable name `{key}`, only letters, digits and `_` are allowed"),
+            ));
+        }
+
+        let value = if let Some(quoted) = value
+            .strip_prefix('\'')
+            .and_then(|value| value.strip_suffix('\''))
+        {
+            quoted.to_owned()
+        } else if let Some(quoted) = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+        {
+            unescape(quoted)
+        } else if value.starts_with(['\'', '"']) {
+            return Err((
+                number,
+                "unterminated quote, multiline values are not supported".to_owned(),
+            ));
+        } else {
+            value.to_owned()
+        };
+
+        // Later lines win
+        env.retain(|(other, _)| other != key);
+        env.push((key.to_owned(), value));
+    }
+
+    Ok(env)
+}
+
+/// As foreman does: `\n` is a newline, then `\x` is `x` for any other
+/// character but a newline
+fn unescape(value: &str) -> String {
+    let value = value.replace("\\n", "\n");
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && next != '\n' => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_processes() {
+        let text = "# dev\n\nweb: rails server -p $PORT\n  worker :  sidekiq -q a:b  \n";
+
+        assert_eq!(
+            parse(text),
+            Ok(pairs(&[
+                ("web", "rails server -p $PORT"),
+                ("worker", "sidekiq -q a:b"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_errors_report_their_line() {
+        assert_eq!(parse("web: a\nnope").unwrap_err().0, 2);
+        assert_eq!(parse("web:").unwrap_err().0, 1);
+        assert_eq!(parse("a b: c").unwrap_err().0, 1);
+        assert_eq!(parse("web: a\n\nweb: b").unwrap_err().0, 3);
+        assert_eq!(parse("web: a \\").unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn parse_env_quoting() {
+        let text = "\
+A=plain value
+B='single \\n \"kept\"'
+C=\"a\\nb \\\"q\\\" \\\\x\"
+# comment
+D=
+A=again
+";
+
+        assert_eq!(
+            parse_env(text),
+            Ok(pairs(&[
+                ("B", "single \\n \"kept\""),
+                ("C", "a\nb \"q\" \\x"),
+                ("D", ""),
+                ("A", "again"),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_env_errors() {
+        assert_eq!(parse_env("export A=1").unwrap_err().0, 1);
+        assert_eq!(parse_env("A=1\nB").unwrap_err().0, 2);
+        assert_eq!(parse_env("A-B=1").unwrap_err().0, 1);
+        assert_eq!(parse_env("A=\"open").unwrap_err().0, 1);
+    }
+
+    #[test]
+    fn unescape_like_foreman() {
+        assert_eq!(unescape(r"a\nb"), "a\nb");
+        assert_eq!(unescape(r#"\"\$x\\"#), r#""$x\"#);
+        assert_eq!(unescape(r"\\n"), "\\\n");
+        assert_eq!(unescape("end\\"), "end\\");
+    }
+
+    #[test]
+    fn ports_start_from_env() {
+        assert_eq!(ports(None, 3), Some(vec![5000, 5100, 5200]));
+        assert_eq!(ports(Some("3000"), 2), Some(vec![3000, 3100]));
+        assert_eq!(ports(Some("65500"), 1), Some(vec![65500]));
+        assert_eq!(ports(Some("65500"), 2), None);
+        assert_eq!(ports(Some("http"), 1), None);
+    }
+}
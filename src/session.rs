@@ -0,0 +1,169 @@
+//! Saving the jobs of a directory when quitting, to recover them on the next
+//! run from the welcome screen.
+//!
+//! Sessions are kept in `$XDG_STATE_HOME/buj/sessions`, one file per
+//! directory buj ran from, named after the path of the directory.
+
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::job::shell::Shell;
+use crate::job::{Job, RestartPolicy};
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+    #[error("Cannot find the state directory, $HOME is not set")]
+    NoStateDir,
+    #[error("Cannot access the saved session: {0}")]
+    Io(#[from] io::Error),
+    #[error("Saved session is corrupted: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Cannot save the session: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    #[serde(default)]
+    job: Vec<SavedJob>,
+}
+
+/// A job as it's saved, in the order of the job list
+#[derive(Serialize, Deserialize)]
+pub struct SavedJob {
+    pub title: String,
+    pub cmd: String,
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub env: Vec<(String, String)>,
+    #[serde(default)]
+    pub clean_env: bool,
+    pub shell: Option<Shell>,
+    pub restart: Option<String>,
+    /// Was running when buj quit
+    #[serde(default)]
+    pub running: bool,
+}
+
+impl SavedJob {
+    pub fn into_job(self) -> Job {
+        let mut job = Job::new(&self.cmd);
+        job.restart.policy = self.policy();
+        job.title = self.title;
+        job.cwd = self.cwd;
+        job.env = self.env;
+        job.clean_env = self.clean_env;
+        job.shell = self.shell;
+        job
+    }
+
+    pub fn policy(&self) -> RestartPolicy {
+        self.restart
+            .as_deref()
+            .and_then(|policy| policy.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Started once recovered: it was running, or must always run
+    pub fn should_start(&self) -> bool {
+        self.running || self.policy() == RestartPolicy::Always
+    }
+}
+
+/// Session file of the current directory
+fn path() -> Result<PathBuf, SessionError> {
+    let state = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .filter(|dir| !dir.is_empty())
+                .map(|home| PathBuf::from(home).join(".local/state"))
+        })
+        .ok_or(SessionError::NoStateDir)?;
+
+    let cwd = std::env::current_dir()?;
+
+    Ok(state.join("buj/sessions").join(file_name(&cwd)))
+}
+
+/// `/home/me/app` is saved as `%2Fhome%2Fme%2Fapp.toml`, percent-encoded so
+/// two directories never share a file
+fn file_name(dir: &Path) -> String {
+    let mut name = String::new();
+
+    for &byte in dir.as_os_str().as_bytes() {
+        if byte == b'%' || byte == b'/' || !(byte.is_ascii_graphic() || byte == b' ') {
+            name.push_str(&format!("%{byte:02X}"));
+        } else {
+            name.push(char::from(byte));
+        }
+    }
+
+    name + ".toml"
+}
+
+/// Save `jobs`, replacing the session of the current directory
+pub fn save(jobs: &[Job]) -> Result<(), SessionError> {
+    let session = Session {
+        job: jobs
+            .iter()
+            .map(|job| SavedJob {
+                title: job.title.clone(),
+                cmd: job.cmd.clone(),
+                cwd: job.cwd.clone(),
+                env: job.env.clone(),
+                clean_env: job.clean_env,
+                shell: job.shell.clone(),
+                restart: Some(job.restart.policy.to_string()),
+                running: job.is_alive(),
+            })
+            .collect(),
+    };
+
+    let path = path()?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    std::fs::write(path, toml::to_string(&session)?)?;
+
+    Ok(())
+}
+
+/// Jobs saved for the current directory, `None` if there are none
+pub fn load() -> Result<Option<Vec<SavedJob>>, SessionError> {
+    let text = match std::fs::read_to_string(path()?) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    let session: Session = toml::from_str(&text)?;
+
+    Ok(Some(session.job).filter(|jobs| !jobs.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_are_readable() {
+        assert_eq!(
+            file_name(Path::new("/home/me/my app")),
+            "%2Fhome%2Fme%2Fmy app.toml"
+        );
+    }
+
+    #[test]
+    fn file_names_dont_collide() {
+        assert_ne!(file_name(Path::new("/a%/b")), file_name(Path::new("/a/%b")));
+        assert_ne!(file_name(Path::new("/a/b")), file_name(Path::new("/a%2Fb")));
+    }
+}
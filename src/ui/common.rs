@@ -1,9 +1,22 @@
 use std::cmp::Ordering;
+use std::time::Duration;
 
 use crate::animation::{AnimationTick, AnimationTicker};
 
 use super::prelude::*;
 
+/// Human readable duration: `850ms`, `12s`, `3m 20s`, `1h 5m`
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..60 => format!("{secs}s"),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
 pub fn pill(bg: Color, mut area: Rect, buf: &mut Buffer) -> Rect {
     if bg != Color::Reset && area.width > 0 {
         buf[(area.left(), area.y)].set_symbol("◖").set_fg(bg);
@@ -1,43 +1,291 @@
+pub mod copy;
+pub mod log;
+pub mod panes;
+
 use crossterm::event::KeyCode;
 
-use crate::app::{App, PopupsState};
-use crate::job::Job;
+use regex::{Regex, RegexBuilder};
+
+use crate::app::{App, InputMode, Notice, PopupsState};
+use crate::job::{Job, JobStatus};
+use crate::keys;
 use crate::ui::prelude::*;
-use crate::vterm;
+use crate::vterm::{self, SearchInfo};
+
+/// Key that gives control back to buj while on passthrough (tmux-like `C-b`)
+const PREFIX_KEY: char = 'b';
+
+/// Lines scrolled by each mouse wheel step
+const WHEEL_LINES: isize = 3;
+
+fn is_prefix_key(key: KeyEvent) -> bool {
+    key.code == KeyCode::Char(PREFIX_KEY) && key.modifiers == KeyModifiers::CONTROL
+}
+
+/// Lines to scroll back for `key`, if it's a scrollback key
+fn scroll_delta(key: KeyEvent, page: u16) -> Option<isize> {
+    let page = page.saturating_sub(1).max(1).casted::<isize>();
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+
+    match key.code {
+        KeyCode::PageUp => Some(page),
+        KeyCode::PageDown => Some(-page),
+        KeyCode::Up if shift => Some(1),
+        KeyCode::Down if shift => Some(-1),
+        _ => None,
+    }
+}
+
+/// Forward events to the current job while on passthrough mode.
+///
+/// Returns [`Action::Noop`] when the event must be handled by buj bindings.
+pub async fn handle_passthrough(state: &mut App, event: Event) -> Action {
+    let Some(job) = state.current_job().filter(|job| job.running.is_some()) else {
+        state.mode = InputMode::Normal;
+        return Action::Tick;
+    };
+
+    let modes = job.input_modes();
+
+    let bytes = match event {
+        Event::Key(key) if key.kind == KeyEventKind::Release => return Action::Intercept,
+        Event::Key(key) if state.mode == InputMode::Prefix => {
+            if is_prefix_key(key) {
+                state.mode = InputMode::Passthrough;
+                keys::encode_key(key, modes)
+            } else if key.code == KeyCode::Esc {
+                state.mode = InputMode::Passthrough;
+                return Action::Tick;
+            } else {
+                // Let buj handle the key
+                state.mode = InputMode::Normal;
+                return Action::Noop;
+            }
+        }
+        Event::Key(key) if is_prefix_key(key) => {
+            state.mode = InputMode::Prefix;
+            return Action::Tick;
+        }
+        // Like on most terminals, shift + page up/down scroll instead of
+        // reaching the program
+        Event::Key(key)
+            if key.modifiers.contains(KeyModifiers::SHIFT)
+                && matches!(key.code, KeyCode::PageUp | KeyCode::PageDown) =>
+        {
+            return Action::Noop;
+        }
+        Event::Key(key) => keys::encode_key(key, modes),
+        // Scrolling and selecting stay on buj
+        Event::Mouse(mouse)
+            if matches!(
+                mouse.kind,
+                MouseEventKind::ScrollUp
+                    | MouseEventKind::ScrollDown
+                    | MouseEventKind::Down(MouseButton::Left)
+            ) =>
+        {
+            return Action::Noop;
+        }
+        Event::Paste(text) => Some(keys::encode_paste(&text, modes)),
+        Event::Resize(_, _) => return Action::Tick,
+        _ => return Action::Intercept,
+    };
+
+    if let Some(bytes) = bytes
+        && let Some(job) = state.current_job_mut()
+        && job.write_input(&bytes).is_err()
+    {
+        state.mode = InputMode::Normal;
+        return Action::Tick;
+    }
+
+    Action::Intercept
+}
+
+/// Search state of the current job, if searching
+pub fn search_info(state: &App) -> Option<SearchInfo> {
+    state.current_job()?.running.as_ref()?.screen.load().search
+}
+
+/// Build the search regex, plain text is escaped. Case insensitive unless
+/// the query has uppercase letters.
+///
+/// Returns `None` for empty queries.
+fn search_regex(query: &str, is_regex: bool) -> Result<Option<Regex>, regex::Error> {
+    if query.is_empty() {
+        return Ok(None);
+    }
+
+    let pattern = if is_regex {
+        query.to_owned()
+    } else {
+        regex::escape(query)
+    };
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!query.chars().any(char::is_uppercase))
+        .build()
+        .map(Some)
+}
+
+/// Handle events while typing on the search prompt
+pub fn handle_search(state: &mut App, event: Event) -> Action {
+    let key = match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => key,
+        Event::Resize(_, _) => return Action::Tick,
+        _ => return Action::Intercept,
+    };
+
+    match key.code {
+        KeyCode::Esc => state.mode = InputMode::Normal,
+        KeyCode::Tab => state.search_regex ^= true,
+        KeyCode::Enter => {
+            state.mode = InputMode::Normal;
+
+            match search_regex(&state.search.content, state.search_regex) {
+                Ok(regex) => {
+                    if let Some(running) = state.current_job().and_then(|job| job.running.as_ref())
+                    {
+                        running.search(regex);
+                    }
+                }
+                Err(err) => state.notice = Some(Notice::error(err)),
+            }
+        }
+        _ => {
+            state.search.handle_key(key);
+        }
+    }
+
+    Action::Tick
+}
 
 impl Component for Job {
     type State = App;
 
     async fn handle_key_events(state: &mut Self::State, key: KeyEvent) -> Action {
+        if state.log.open {
+            return log::handle_key(state, key);
+        }
+
+        if let Some(job) = state.current_job()
+            && let Some(ref running) = job.running
+        {
+            if let Some(delta) = scroll_delta(key, job.size.height) {
+                running.scroll(delta);
+                return Action::Tick;
+            }
+
+            if key.code == KeyCode::End {
+                running.follow();
+                return Action::Tick;
+            }
+
+            let searching = running.screen.load().search.is_some();
+
+            match key.code {
+                KeyCode::Char('/') => {
+                    state.search.select_all();
+                    state.mode = InputMode::Search;
+                    return Action::Tick;
+                }
+                KeyCode::Char('n') if searching => {
+                    running.search_step(true);
+                    return Action::Tick;
+                }
+                KeyCode::Char('N') if searching => {
+                    running.search_step(false);
+                    return Action::Tick;
+                }
+                KeyCode::Esc if searching => {
+                    running.search(None);
+                    return Action::Tick;
+                }
+                KeyCode::Char('v') => return copy::enter(state),
+                _ => {}
+            }
+        }
+
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        let direction = match key.code {
+            KeyCode::Left => Some(panes::Direction::Left),
+            KeyCode::Right => Some(panes::Direction::Right),
+            KeyCode::Up => Some(panes::Direction::Up),
+            KeyCode::Down => Some(panes::Direction::Down),
+            _ => None,
+        };
+
         match key.code {
-            KeyCode::Enter => Action::Noop,
+            KeyCode::Char('|') => return panes::split(state, panes::Split::Horizontal),
+            KeyCode::Char('-') => return panes::split(state, panes::Split::Vertical),
+            KeyCode::Char('#') => return panes::split(state, panes::Split::Grid),
+            KeyCode::Char('x') => return panes::close(state),
+            KeyCode::Char('w') => return panes::focus_next(state),
+            _ if let Some(direction) = direction
+                && alt =>
+            {
+                return panes::focus_towards(state, direction);
+            }
+            _ if let Some(direction) = direction
+                && ctrl =>
+            {
+                return panes::resize(state, direction);
+            }
+            _ => {}
+        }
+
+        match key.code {
+            KeyCode::Enter if state.current_job().is_some_and(|job| job.running.is_some()) => {
+                state.mode = InputMode::Passthrough;
+                Action::Tick
+            }
+            // Welcome screen
+            KeyCode::Enter if state.jobs.is_empty() => {
+                state.recover_session();
+                Action::Tick
+            }
+            // The "All jobs" view sits before the first job
             KeyCode::Tab => {
-                state.current_job = state
-                    .current_job
-                    .take()
-                    .map_or(0, |i| i + 1)
-                    .checked_rem(state.jobs.len());
+                match state.current_job.map_or(0, |i| i + 1) {
+                    _ if state.jobs.is_empty() => state.current_job = None,
+                    next if next < state.jobs.len() => state.current_job = Some(next),
+                    _ => log::open(state),
+                }
 
                 Action::Tick
             }
             KeyCode::BackTab => {
-                state.current_job = state
-                    .current_job
-                    .take()
-                    .unwrap_or_else(|| state.jobs.len().min(1))
-                    .checked_sub(1)
-                    .or_else(|| state.jobs.len().checked_sub(1));
+                match state.current_job {
+                    _ if state.jobs.is_empty() => state.current_job = None,
+                    Some(0) => log::open(state),
+                    Some(i) => state.current_job = Some(i - 1),
+                    None => state.current_job = Some(0),
+                }
 
                 Action::Tick
             }
             KeyCode::Char('k') if let Some(job) = state.current_job_mut() => {
-                job.kill();
+                job.stop();
+                Action::Tick
+            }
+            KeyCode::Char('p') if let Some(job) = state.current_job_mut() => {
+                job.restart.policy = job.restart.policy.next();
+                Action::Tick
+            }
+            KeyCode::Char('S') if let Some(job) = state.current_job_mut() => {
+                job.next_stop_signal();
                 Action::Tick
             }
             KeyCode::Char('m') if state.current_job.is_some() => {
                 PopupsState::open::<popup::RenamePopup>(state);
                 Action::Tick
             }
+            KeyCode::Char('i') if state.current_job.is_some() => {
+                PopupsState::open::<popup::DetailsPopup>(state);
+                Action::Tick
+            }
             KeyCode::Char('e') if state.current_job.is_some() => {
                 PopupsState::open::<popup::EditPopup>(state);
                 Action::Tick
@@ -57,6 +305,29 @@ impl Component for Job {
         }
     }
 
+    async fn handle_mouse_events(state: &mut Self::State, mouse: MouseEvent) -> Action {
+        if state.log.open {
+            return log::handle_mouse(state, mouse);
+        }
+
+        let delta = match mouse.kind {
+            MouseEventKind::ScrollUp => WHEEL_LINES,
+            MouseEventKind::ScrollDown => -WHEEL_LINES,
+            MouseEventKind::Down(MouseButton::Left) => {
+                panes::focus_at(state, mouse);
+                return copy::start_drag(state, mouse);
+            }
+            _ => return Action::Noop,
+        };
+
+        if let Some(running) = state.current_job().and_then(|job| job.running.as_ref()) {
+            running.scroll(delta);
+            return Action::Tick;
+        }
+
+        Action::Noop
+    }
+
     fn draw(state: &mut Self::State, frame: &mut Frame, area: Rect) {
         frame.render_widget(
             Block::new()
@@ -73,13 +344,27 @@ impl Component for Job {
         ])
         .split(area.inner(Margin::both(1)));
 
-        if state.current_job.is_some() {
+        if state.log.open {
+            frame.draw(log::render_help, area[0], state);
+        } else if state.current_job.is_some() {
             frame.draw(render_help, area[0], state);
         }
 
         frame.draw(render_loading_bar, area[0], state);
 
-        frame.draw(render_job, area[1], state);
+        if state.log.open {
+            frame.draw(log::render, area[1], state);
+        } else {
+            frame.draw(panes::render, area[1], state);
+        }
+
+        match state.mode {
+            InputMode::Search => {
+                frame.draw(render_search_prompt, state.panes.focused_area(), state);
+            }
+            InputMode::Copy => frame.draw(copy::render, state.vterm_area, state),
+            _ => {}
+        }
 
         frame.draw(render_footer, area[2], state);
         frame.draw(render_loading_bar, area[2], state);
@@ -135,6 +420,41 @@ fn render_loading_bar(state: &App, area: Rect, buf: &mut Buffer) {
 fn render_help(state: &App, area: Rect, buf: &mut Buffer) {
     let area = area.inner(Margin::horizontal(1));
 
+    if state.mode != InputMode::Normal {
+        let (mode, hint) = match state.mode {
+            InputMode::Search => ("SEARCH", " <Enter> find <Tab> text/regex <Esc> cancel"),
+            InputMode::Copy => ("COPY", " v select V lines y copy <Esc> back"),
+            InputMode::Prefix => ("PASSTHROUGH", " waiting key, <Esc> back to job"),
+            _ => ("PASSTHROUGH", " <C-b> buj keys"),
+        };
+
+        Line::from(vec![
+            mode.to_span().style(state.theme.accent),
+            hint.to_span().style(state.theme.normal),
+        ])
+        .render(area, buf);
+
+        return;
+    }
+
+    if search_info(state).is_some() {
+        Line::from(vec![
+            "SEARCH".to_span().style(state.theme.accent),
+            " ".to_span(),
+            "n".to_span().style(state.theme.keybind_accent),
+            "ext ".to_span().style(state.theme.normal),
+            "N".to_span().style(state.theme.keybind_accent),
+            " previous ".to_span().style(state.theme.normal),
+            "/".to_span().style(state.theme.keybind_accent),
+            " new search ".to_span().style(state.theme.normal),
+            "<Esc>".to_span().style(state.theme.keybind_accent),
+            " clear".to_span().style(state.theme.normal),
+        ])
+        .render(area, buf);
+
+        return;
+    }
+
     Line::from(vec![
         "r".to_span().style(state.theme.keybind_accent),
         "estart ".to_span().style(state.theme.normal),
@@ -145,18 +465,55 @@ fn render_help(state: &App, area: Rect, buf: &mut Buffer) {
         "rena".to_span().style(state.theme.normal),
         "m".to_span().style(state.theme.keybind_accent),
         "e ".to_span().style(state.theme.normal),
+        "p".to_span().style(state.theme.keybind_accent),
+        "olicy ".to_span().style(state.theme.normal),
+        "S".to_span().style(state.theme.keybind_accent),
+        "ignal ".to_span().style(state.theme.normal),
+        "i".to_span().style(state.theme.keybind_accent),
+        "nfo ".to_span().style(state.theme.normal),
+        "v".to_span().style(state.theme.keybind_accent),
+        " copy".to_span().style(state.theme.normal),
     ])
     .render(area, buf);
+
+    if let Some(job) = state.current_job() {
+        Line::from(job.restart.policy.to_span().style(state.theme.normal))
+            .right_aligned()
+            .render(area, buf);
+    }
 }
 
-fn render_job(state: &mut App, frame: &mut Frame, area: Rect) {
-    let area = area.inner(Margin::both(1));
+fn render_search_prompt(state: &mut App, frame: &mut Frame, area: Rect) {
+    let area = area
+        .inner(Margin::horizontal(1))
+        .offset(Offset::y(area.height.saturating_sub(3).casted::<i32>()))
+        .set_height(3);
 
-    if let Some(job) = state.current_job_mut() {
-        render_vterm(job, frame, area);
-    } else if state.anim.render_blink {
-        render_welcome_screen(state, area, frame.buffer_mut());
-    }
+    frame.render_widget(Clear, area);
+
+    let label = if state.search_regex { "regex /" } else { "/" };
+
+    let [label_area, input_area] = Layout::horizontal([
+        Constraint::Length(label.len() as u16),
+        Constraint::Percentage(100),
+    ])
+    .split(area);
+
+    frame.draw_stateless(
+        Text::raw(label).style(state.theme.accent),
+        label_area.inner_y(1),
+    );
+
+    // The input adds its own margin
+    frame.draw(
+        common::Input::default().border_style(state.theme.border),
+        Rect {
+            x: input_area.x - 1,
+            width: input_area.width + 1,
+            ..input_area
+        },
+        &mut state.search,
+    );
 }
 
 fn render_welcome_screen(state: &mut App, area: Rect, buf: &mut Buffer) {
@@ -198,47 +555,110 @@ fn render_welcome_screen(state: &mut App, area: Rect, buf: &mut Buffer) {
     .render(area, buf);
 }
 
-fn render_vterm(job: &mut Job, frame: &mut Frame, area: Rect) {
+/// Draw the terminal of `job`, `resize` gives the PTY the size of `area`
+fn render_vterm(job: &mut Job, frame: &mut Frame, area: Rect, resize: bool) {
     let size = area.as_size();
-    job.size = size;
+    let resized = resize && job.size != size;
+
+    if resize {
+        job.size = size;
+    }
+
     if let Some(ref job) = job.running {
-        _ = job.pty.resize(portable_pty::PtySize {
-            rows: size.height,
-            cols: size.width,
-            pixel_width: 0,
-            pixel_height: 0,
-        });
-        job.vterm.blocking_write().set_size(size.height, size.width);
-        frame.render_widget(
-            vterm::VTermWidget::new(job.vterm.blocking_read().screen()),
-            area,
-        );
+        if resized {
+            job.resize(size);
+        }
+
+        let screen = job.screen();
+        frame.render_widget(vterm::VTermWidget::new(&screen), area);
+
+        if screen.scrollback > 0 {
+            render_scrollback_indicator(screen.scrollback, area, frame.buffer_mut());
+        }
     } else {
         frame.render_widget(Text::from("No running job"), area);
     }
 }
 
+/// Pill on the top right corner telling how far back the view is
+fn render_scrollback_indicator(lines: usize, area: Rect, buf: &mut Buffer) {
+    let text = format!("scrolled {lines} lines back, <End> follow");
+    let width = (text.chars().count() as u16 + 2).min(area.width);
+
+    let pill_area = area
+        .set_height(1)
+        .offset(Offset::x(area.width.saturating_sub(width).casted::<i32>()));
+    let pill_area = pill_area.set_width(width);
+
+    common::pill(Color::Yellow, pill_area, buf);
+
+    Text::raw(text)
+        .fg(Color::Black)
+        .bg(Color::Yellow)
+        .render(pill_area.inner(Margin::horizontal(1)), buf);
+}
+
 fn render_footer(state: &App, frame: &mut Frame, area: Rect) {
     let area = area.inner(Margin::horizontal(1));
-    frame.draw_stateless(
-        Line::from("Apika Luca".to_span().style(state.theme.accent))
-            .centered()
-            .bold(),
-        area,
-    );
 
-    let Some(status) = state.current_job().and_then(Job::status) else {
+    let center = if let Some(ref notice) = state.notice {
+        let fg = if notice.error {
+            Color::LightRed
+        } else {
+            Color::LightGreen
+        };
+        let text = notice.text.to_span().fg(fg);
+
+        // Keep the start of long messages (errors with a location) readable
+        if text.width() > usize::from(area.width) {
+            frame.draw_stateless(Line::from(text).bold(), area);
+            return;
+        }
+
+        text
+    } else if state.log.open {
+        Span::styled(log::filter_label(state), state.theme.accent)
+    } else if let Some(search) = search_info(state) {
+        let text = match search.current {
+            Some(current) => format!("match {} of {}", current + 1, search.total),
+            None => "no matches".to_owned(),
+        };
+
+        Span::styled(text, state.theme.accent)
+    } else {
+        "Apika Luca".to_span().style(state.theme.accent)
+    };
+
+    frame.draw_stateless(Line::from(center).centered().bold(), area);
+
+    let Some(job) = state.current_job().filter(|_| !state.log.open) else {
         return;
     };
 
     let buf = frame.buffer_mut();
 
-    let left0 = "Exit code: ";
-    let left1 = status.to_string();
-    let right = "200ms";
+    let right = match job.runtime() {
+        Some(runtime) if job.past_runtime.is_zero() => common::format_duration(runtime),
+        Some(runtime) => format!(
+            "{} (total {})",
+            common::format_duration(runtime),
+            common::format_duration(job.total_runtime())
+        ),
+        None => String::new(),
+    };
+
+    let status = job.status();
+
+    if matches!(status, JobStatus::NotStarted) {
+        Line::raw(right).right_aligned().bold().render(area, buf);
+        return;
+    }
+
+    let left0 = status.kind();
+    let left1 = status.label();
 
     let area = Layout::horizontal([
-        Constraint::Length(left0.len() as u16 + left1.len() as u16 + 2),
+        Constraint::Length(left0.len() as u16 + left1.chars().count() as u16 + 2),
         Constraint::Length(right.len() as u16),
     ])
     .flex(Flex::SpaceBetween)
@@ -247,20 +667,16 @@ fn render_footer(state: &App, frame: &mut Frame, area: Rect) {
     Line::raw(left0).bold().render(area[0], buf);
     Line::raw(right).right_aligned().bold().render(area[1], buf);
 
-    let exit_color = if status == 0 {
-        Color::Green
-    } else {
-        Color::Red
-    };
+    let status_color = status.color();
 
-    let exit_area = area[0]
+    let status_area = area[0]
         .offset(Offset::x(left0.len().casted::<i32>()))
-        .set_width(left1.len() as u16 + 2);
+        .set_width(left1.chars().count() as u16 + 2);
 
-    common::pill(exit_color, exit_area, buf);
+    common::pill(status_color, status_area, buf);
 
     Text::raw(left1)
         .bold()
-        .bg(exit_color)
-        .render(exit_area.inner(Margin::horizontal(1)), buf);
+        .bg(status_color)
+        .render(status_area.inner(Margin::horizontal(1)), buf);
 }
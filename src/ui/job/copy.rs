@@ -0,0 +1,322 @@
+//! Copy mode: select text of the current job, scrollback included, with vi
+//! keys or a mouse drag and copy it to the clipboard.
+//!
+//! Positions are `(line, col)`, lines are indexed like [`vterm::Snapshot::top`].
+
+use crate::app::{App, InputMode, Notice};
+use crate::clipboard;
+use crate::ui::prelude::*;
+use crate::vterm::{self, Snapshot};
+
+#[derive(Default)]
+pub struct CopyMode {
+    cursor: (usize, u16),
+    /// Other end of the selection
+    anchor: Option<(usize, u16)>,
+    /// Select whole lines
+    whole_lines: bool,
+    /// Selection started with the mouse, released copies it
+    dragging: bool,
+    /// Mode restored when leaving
+    previous: InputMode,
+}
+
+impl CopyMode {
+    /// Start and end of the selection, ordered
+    fn selection(&self) -> Option<((usize, u16), (usize, u16))> {
+        let anchor = self.anchor?;
+        let (from, to) = if anchor <= self.cursor {
+            (anchor, self.cursor)
+        } else {
+            (self.cursor, anchor)
+        };
+
+        if self.whole_lines {
+            Some(((from.0, 0), (to.0, u16::MAX)))
+        } else {
+            Some((from, to))
+        }
+    }
+
+    /// Selected columns of `line`, end included
+    fn selected_cols(&self, line: usize, cols: u16) -> Option<(u16, u16)> {
+        let (from, to) = self.selection()?;
+
+        if !(from.0..=to.0).contains(&line) {
+            return None;
+        }
+
+        let start = if line == from.0 { from.1 } else { 0 };
+        let end = if line == to.0 { to.1 } else { u16::MAX };
+
+        Some((start, end.min(cols.saturating_sub(1))))
+    }
+}
+
+fn current_screen(state: &App) -> Option<std::sync::Arc<Snapshot>> {
+    Some(state.current_job()?.running.as_ref()?.screen())
+}
+
+/// Enter copy mode with the cursor on the terminal cursor
+pub fn enter(state: &mut App) -> Action {
+    let Some(screen) = current_screen(state) else {
+        return Action::Noop;
+    };
+
+    let line = if screen.scrollback == 0 {
+        screen.top + usize::from(screen.cursor.0)
+    } else {
+        screen.top
+    };
+
+    state.copy = CopyMode {
+        cursor: (line, 0),
+        previous: state.mode,
+        ..CopyMode::default()
+    };
+    state.mode = InputMode::Copy;
+
+    Action::Tick
+}
+
+fn leave(state: &mut App) {
+    state.mode = state.copy.previous;
+    state.copy.anchor = None;
+    state.copy.dragging = false;
+}
+
+/// Cursor position under the mouse, rows outside of the terminal are clamped
+/// to the first or last one
+fn mouse_position(state: &App, screen: &Snapshot, mouse: MouseEvent) -> (usize, u16) {
+    let area = state.vterm_area;
+    let (rows, cols) = screen.size();
+
+    let row = mouse
+        .row
+        .saturating_sub(area.y)
+        .min(area.height.min(rows).saturating_sub(1));
+    let col = mouse
+        .column
+        .saturating_sub(area.x)
+        .min(area.width.min(cols).saturating_sub(1));
+
+    let line = screen.top + usize::from(row);
+
+    // Scroll when dragging past the edges
+    let line = if mouse.row < area.y {
+        line.saturating_sub(1)
+    } else if mouse.row >= area.bottom() {
+        line + 1
+    } else {
+        line
+    };
+
+    (line, col)
+}
+
+/// Start a mouse selection
+pub fn start_drag(state: &mut App, mouse: MouseEvent) -> Action {
+    if !state
+        .vterm_area
+        .contains(Position::new(mouse.column, mouse.row))
+    {
+        return Action::Noop;
+    }
+
+    if state.mode != InputMode::Copy {
+        _ = enter(state);
+    }
+
+    let Some(screen) = current_screen(state) else {
+        return Action::Noop;
+    };
+
+    let position = mouse_position(state, &screen, mouse);
+
+    state.copy.cursor = position;
+    state.copy.anchor = Some(position);
+    state.copy.whole_lines = false;
+    state.copy.dragging = true;
+
+    Action::Tick
+}
+
+/// Copy the selection to the clipboard and leave copy mode
+async fn yank(state: &mut App) {
+    let selection = state.copy.selection();
+    leave(state);
+
+    let Some((from, to)) = selection else {
+        return;
+    };
+
+    let Some(running) = state.current_job().and_then(|job| job.running.as_ref()) else {
+        return;
+    };
+
+    let Some(text) = running.text(from, to).await else {
+        return;
+    };
+
+    state.notice = Some(
+        match clipboard::copy(&text, state.clipboard_command.as_ref()) {
+            Ok(()) => {
+                let lines = text.lines().count();
+                Notice::info(format!(
+                    "Copied {lines} line{}",
+                    if lines == 1 { "" } else { "s" }
+                ))
+            }
+            Err(err) => Notice::error(format!("Cannot copy: {err}")),
+        },
+    );
+}
+
+/// Handle events while on copy mode
+pub async fn handle_event(state: &mut App, event: Event) -> Action {
+    let Some(screen) = current_screen(state) else {
+        leave(state);
+        return Action::Tick;
+    };
+
+    match event {
+        Event::Key(key) if key.kind != KeyEventKind::Release => {
+            handle_key(state, &screen, key).await
+        }
+        Event::Mouse(mouse) => match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => start_drag(state, mouse),
+            MouseEventKind::Drag(MouseButton::Left) if state.copy.dragging => {
+                state.copy.cursor = mouse_position(state, &screen, mouse);
+                reveal(state);
+                Action::Tick
+            }
+            MouseEventKind::Up(MouseButton::Left) if state.copy.dragging => {
+                state.copy.dragging = false;
+
+                if state.copy.anchor == Some(state.copy.cursor) {
+                    // Just a click
+                    leave(state);
+                } else {
+                    yank(state).await;
+                }
+
+                Action::Tick
+            }
+            // Let the wheel scroll
+            MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => Action::Noop,
+            _ => Action::Intercept,
+        },
+        Event::Resize(_, _) => Action::Tick,
+        _ => Action::Intercept,
+    }
+}
+
+async fn handle_key(state: &mut App, screen: &Snapshot, key: KeyEvent) -> Action {
+    let (rows, cols) = screen.size();
+    let last_line = (screen.dropped + screen.history + usize::from(rows)).saturating_sub(1);
+    let page = usize::from(rows.max(1));
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    let copy = &mut state.copy;
+    let (line, col) = copy.cursor;
+
+    copy.cursor = match key.code {
+        KeyCode::Esc if copy.anchor.is_some() => {
+            copy.anchor = None;
+            return Action::Tick;
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            leave(state);
+            return Action::Tick;
+        }
+        KeyCode::Char('y') | KeyCode::Enter => {
+            yank(state).await;
+            return Action::Tick;
+        }
+        KeyCode::Char('v') => {
+            copy.anchor = (copy.anchor.is_none() || copy.whole_lines).then_some(copy.cursor);
+            copy.whole_lines = false;
+            return Action::Tick;
+        }
+        KeyCode::Char('V') => {
+            copy.anchor = (copy.anchor.is_none() || !copy.whole_lines).then_some(copy.cursor);
+            copy.whole_lines = true;
+            return Action::Tick;
+        }
+
+        KeyCode::Char('u') if ctrl => (line.saturating_sub(page / 2), col),
+        KeyCode::Char('d') if ctrl => (line + page / 2, col),
+        KeyCode::Char('b') if ctrl => (line.saturating_sub(page), col),
+        KeyCode::Char('f') if ctrl => (line + page, col),
+        KeyCode::PageUp => (line.saturating_sub(page), col),
+        KeyCode::PageDown => (line + page, col),
+
+        KeyCode::Char('h') | KeyCode::Left => (line, col.saturating_sub(1)),
+        KeyCode::Char('l') | KeyCode::Right => (line, col + 1),
+        KeyCode::Char('k') | KeyCode::Up => (line.saturating_sub(1), col),
+        KeyCode::Char('j') | KeyCode::Down => (line + 1, col),
+        KeyCode::Char('0') | KeyCode::Home => (line, 0),
+        KeyCode::Char('$') | KeyCode::End => (line, line_end(screen, line)),
+        KeyCode::Char('g') => (screen.dropped, 0),
+        KeyCode::Char('G') => (last_line, 0),
+        _ => return Action::Intercept,
+    };
+
+    copy.cursor.0 = copy.cursor.0.clamp(screen.dropped, last_line);
+    copy.cursor.1 = copy.cursor.1.min(cols.saturating_sub(1));
+
+    reveal(state);
+
+    Action::Tick
+}
+
+/// Column of the last non blank cell of `line`, if visible
+fn line_end(screen: &Snapshot, line: usize) -> u16 {
+    let (_, cols) = screen.size();
+
+    let Some(row) = line
+        .checked_sub(screen.top)
+        .and_then(|row| u16::try_from(row).ok())
+    else {
+        return cols.saturating_sub(1);
+    };
+
+    (0..cols)
+        .rev()
+        .find(|&col| screen.cell(row, col).is_some_and(vterm::Cell::has_contents))
+        .unwrap_or(0)
+}
+
+fn reveal(state: &App) {
+    if let Some(running) = state.current_job().and_then(|job| job.running.as_ref()) {
+        running.reveal(state.copy.cursor.0);
+    }
+}
+
+/// Draw the selection and cursor over the job terminal
+pub fn render(state: &App, area: Rect, buf: &mut Buffer) {
+    let Some(screen) = current_screen(state) else {
+        return;
+    };
+
+    let (rows, cols) = screen.size();
+    let rows = rows.min(area.height);
+    let cols = cols.min(area.width);
+
+    for row in 0..rows {
+        let line = screen.top + usize::from(row);
+
+        if let Some((start, end)) = state.copy.selected_cols(line, cols) {
+            for col in start..=end.min(cols.saturating_sub(1)) {
+                buf[(area.x + col, area.y + row)]
+                    .set_fg(Color::Black)
+                    .set_bg(Color::LightBlue);
+            }
+        }
+
+        if line == state.copy.cursor.0 && state.copy.cursor.1 < cols {
+            let cell = &mut buf[(area.x + state.copy.cursor.1, area.y + row)];
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! "All jobs" view: the combined log of every job, each line prefixed with
+//! the title of its job.
+
+use crate::app::App;
+use crate::job::log::{LOG, LogLine};
+use crate::ui::prelude::*;
+
+/// Color of the job titles, by position of the job
+const COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::LightRed,
+];
+
+/// Titles are padded up to this width
+const MAX_TITLE_WIDTH: usize = 20;
+
+#[derive(Default)]
+pub struct LogView {
+    pub open: bool,
+    /// Only show the lines of this job id
+    filter: Option<u64>,
+    /// Seq of the bottom line while scrolled back, `None` follows the log
+    anchor: Option<u64>,
+}
+
+impl LogView {
+    fn shows(&self, line: &LogLine) -> bool {
+        self.filter.is_none_or(|job| line.job == job)
+    }
+
+    /// Scroll `delta` lines back, keeping at least a page of lines
+    fn scroll(&mut self, delta: isize, page: u16) {
+        let seqs: Vec<u64> = LOG.with_lines(|lines| {
+            lines
+                .iter()
+                .filter(|line| self.shows(line))
+                .map(|line| line.seq)
+                .collect()
+        });
+
+        let Some(newest) = seqs.len().checked_sub(1) else {
+            return;
+        };
+
+        let bottom = self.anchor.map_or(newest, |anchor| {
+            seqs.partition_point(|&seq| seq <= anchor).saturating_sub(1)
+        });
+
+        let oldest_bottom = usize::from(page).saturating_sub(1).min(newest);
+        let bottom = bottom
+            .saturating_add_signed(-delta)
+            .clamp(oldest_bottom, newest);
+
+        self.anchor = (bottom < newest).then(|| seqs[bottom]);
+    }
+}
+
+pub fn open(state: &mut App) {
+    state.log.open = true;
+}
+
+/// Cycle the job filter, `forward` goes down the job list
+fn cycle_filter(state: &mut App, forward: bool) {
+    let position = state
+        .log
+        .filter
+        .and_then(|id| state.jobs.iter().position(|job| job.id == id));
+    let len = state.jobs.len();
+
+    let next = match position {
+        None if forward => Some(0),
+        None => len.checked_sub(1),
+        Some(idx) if forward => Some(idx + 1).filter(|&idx| idx < len),
+        Some(idx) => idx.checked_sub(1),
+    };
+
+    state.log.filter = next.and_then(|idx| state.jobs.get(idx)).map(|job| job.id);
+    state.log.anchor = None;
+}
+
+/// Handle keys while the view is open, other job bindings are disabled
+pub fn handle_key(state: &mut App, key: KeyEvent) -> Action {
+    let page = state.vterm_area.height;
+
+    match key.code {
+        KeyCode::Tab => {
+            state.log.open = false;
+            state.current_job = (!state.jobs.is_empty()).then_some(0);
+        }
+        KeyCode::BackTab => {
+            state.log.open = false;
+            state.current_job = state.jobs.len().checked_sub(1);
+        }
+        KeyCode::Char('f') => cycle_filter(state, true),
+        KeyCode::Char('F') => cycle_filter(state, false),
+        KeyCode::Esc if state.log.filter.is_some() => {
+            state.log.filter = None;
+            state.log.anchor = None;
+        }
+        KeyCode::End => state.log.anchor = None,
+        _ if let Some(delta) = super::scroll_delta(key, page) => state.log.scroll(delta, page),
+        _ => return Action::Intercept,
+    }
+
+    Action::Tick
+}
+
+pub fn handle_mouse(state: &mut App, mouse: MouseEvent) -> Action {
+    let delta = match mouse.kind {
+        MouseEventKind::ScrollUp => super::WHEEL_LINES,
+        MouseEventKind::ScrollDown => -super::WHEEL_LINES,
+        _ => return Action::Intercept,
+    };
+
+    state.log.scroll(delta, state.vterm_area.height);
+    Action::Tick
+}
+
+pub fn render_help(state: &App, area: Rect, buf: &mut Buffer) {
+    let area = area.inner(Margin::horizontal(1));
+
+    Line::from(vec![
+        "ALL JOBS".to_span().style(state.theme.accent),
+        " ".to_span(),
+        "f".to_span().style(state.theme.keybind_accent),
+        "ilter ".to_span().style(state.theme.normal),
+        "F".to_span().style(state.theme.keybind_accent),
+        " previous ".to_span().style(state.theme.normal),
+        "<End>".to_span().style(state.theme.keybind_accent),
+        " follow ".to_span().style(state.theme.normal),
+        "<Tab>".to_span().style(state.theme.keybind_accent),
+        " jobs".to_span().style(state.theme.normal),
+    ])
+    .render(area, buf);
+}
+
+/// Label of the current filter, for the footer
+pub fn filter_label(state: &App) -> String {
+    match state.log.filter {
+        Some(id) => {
+            let title = state
+                .jobs
+                .iter()
+                .find(|job| job.id == id)
+                .map_or("?", |job| job.title.as_str());
+
+            format!("only {title}")
+        }
+        None => "all jobs".to_owned(),
+    }
+}
+
+pub fn render(state: &mut App, area: Rect, buf: &mut Buffer) {
+    let area = area.inner(Margin::both(1));
+    state.vterm_area = area;
+
+    let width = state
+        .jobs
+        .iter()
+        .map(|job| job.title.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(MAX_TITLE_WIDTH);
+
+    let view = &state.log;
+    let height = usize::from(area.height);
+
+    let (lines, scrolled): (Vec<Line>, usize) = LOG.with_lines(|lines| {
+        let scrolled = view.anchor.map_or(0, |anchor| {
+            lines
+                .iter()
+                .rev()
+                .take_while(|line| line.seq > anchor)
+                .filter(|line| view.shows(line))
+                .count()
+        });
+
+        let mut visible: Vec<Line> = lines
+            .iter()
+            .rev()
+            .filter(|line| view.shows(line))
+            .skip_while(|line| view.anchor.is_some_and(|anchor| line.seq > anchor))
+            .take(height)
+            .map(|line| {
+                let (title, color) = state
+                    .jobs
+                    .iter()
+                    .position(|job| job.id == line.job)
+                    .map_or(("?", Color::Reset), |idx| {
+                        (state.jobs[idx].title.as_str(), COLORS[idx % COLORS.len()])
+                    });
+
+                let title: String = title.chars().take(width).collect();
+
+                Line::from(vec![
+                    Span::styled(format!("{title:<width$} | "), Style::new().fg(color)),
+                    Span::raw(line.text.clone()),
+                ])
+            })
+            .collect();
+
+        visible.reverse();
+        (visible, scrolled)
+    });
+
+    if lines.is_empty() {
+        Text::from("No output yet")
+            .style(state.theme.normal)
+            .centered()
+            .render(area.inner_y(i32::from(area.height / 2)), buf);
+        return;
+    }
+
+    Text::from(lines).render(area, buf);
+
+    if scrolled > 0 {
+        super::render_scrollback_indicator(scrolled, area, buf);
+    }
+}
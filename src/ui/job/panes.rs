@@ -0,0 +1,343 @@
+//! Tiling the job area into panes, each one showing a job.
+//!
+//! The focused pane always shows [`App::current_job`], so every binding acting
+//! on the current job acts on the focused pane.
+
+use crate::app::{App, InputMode};
+use crate::ui::prelude::*;
+
+/// Starting weight of every column and row
+const WEIGHT: u16 = 8;
+/// Columns and rows can't shrink past this weight
+const MIN_WEIGHT: u16 = 2;
+const MAX_PANES: usize = 9;
+
+/// How panes are tiled
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Split {
+    /// Panes side by side
+    #[default]
+    Horizontal,
+    /// Panes stacked on top of each other
+    Vertical,
+    /// Rows of panes, as many rows as columns
+    Grid,
+}
+
+/// Direction to move the focus or resize towards
+#[derive(Clone, Copy)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+pub struct Panes {
+    split: Split,
+    /// Job shown on each pane, the focused one uses [`App::current_job`]
+    jobs: Vec<Option<usize>>,
+    focus: usize,
+    /// Weight of each column
+    cols: Vec<u16>,
+    /// Weight of each row
+    rows: Vec<u16>,
+    /// Where each pane was last drawn
+    areas: Vec<Rect>,
+}
+
+impl Default for Panes {
+    fn default() -> Self {
+        Self {
+            split: Split::default(),
+            jobs: vec![None],
+            focus: 0,
+            cols: vec![WEIGHT],
+            rows: vec![WEIGHT],
+            areas: Vec::new(),
+        }
+    }
+}
+
+impl Panes {
+    pub fn is_split(&self) -> bool {
+        self.jobs.len() > 1
+    }
+
+    /// Area of the focused pane, border included
+    pub fn focused_area(&self) -> Rect {
+        self.areas.get(self.focus).copied().unwrap_or_default()
+    }
+
+    /// Jobs shown on any pane
+    pub fn visible_jobs(&self, current_job: Option<usize>) -> impl Iterator<Item = usize> {
+        self.jobs
+            .iter()
+            .enumerate()
+            .filter_map(move |(idx, &job)| if idx == self.focus { current_job } else { job })
+    }
+
+    /// Forget a removed job, shifting the jobs after it
+    pub fn remove_job(&mut self, idx: usize) {
+        for job in &mut self.jobs {
+            *job = match *job {
+                Some(job) if job > idx => Some(job - 1),
+                Some(job) if job == idx => None,
+                job => job,
+            };
+        }
+    }
+
+    /// `(columns, rows)` of the tiling
+    fn grid(&self) -> (usize, usize) {
+        let panes = self.jobs.len();
+
+        match self.split {
+            Split::Horizontal => (panes, 1),
+            Split::Vertical => (1, panes),
+            Split::Grid => {
+                let cols = (1..).find(|cols| cols * cols >= panes).unwrap_or(1);
+                (cols, panes.div_ceil(cols))
+            }
+        }
+    }
+
+    fn reset_weights(&mut self) {
+        let (cols, rows) = self.grid();
+        self.cols = vec![WEIGHT; cols];
+        self.rows = vec![WEIGHT; rows];
+    }
+
+    /// Area of each pane, panes of an incomplete last row fill the row
+    fn layout(&self, area: Rect) -> Vec<Rect> {
+        let (cols, _) = self.grid();
+        let rows =
+            ratatui::layout::Layout::vertical(self.rows.iter().map(|&w| Constraint::Fill(w)))
+                .split(area);
+
+        rows.iter()
+            .enumerate()
+            .flat_map(|(row, &row_area)| {
+                let panes = (self.jobs.len() - row * cols).min(cols);
+
+                ratatui::layout::Layout::horizontal(
+                    self.cols[..panes].iter().map(|&w| Constraint::Fill(w)),
+                )
+                .split(row_area)
+                .to_vec()
+            })
+            .collect()
+    }
+
+    /// Pane at a screen position
+    fn pane_at(&self, position: Position) -> Option<usize> {
+        self.areas.iter().position(|area| area.contains(position))
+    }
+}
+
+/// Give the focus to another pane
+fn focus(state: &mut App, idx: usize) {
+    let panes = &mut state.panes;
+
+    if idx == panes.focus || idx >= panes.jobs.len() {
+        return;
+    }
+
+    panes.jobs[panes.focus] = state.current_job;
+    panes.focus = idx;
+    state.current_job = panes.jobs[idx];
+
+    // Modes act on the job of the pane they started on
+    state.mode = InputMode::Normal;
+}
+
+/// Split the job area once more, the new pane gets the focus and the first
+/// job not shown yet
+pub fn split(state: &mut App, split: Split) -> Action {
+    if state.panes.jobs.len() >= MAX_PANES {
+        return Action::Noop;
+    }
+
+    let visible: Vec<usize> = state.panes.visible_jobs(state.current_job).collect();
+    let job = (0..state.jobs.len()).find(|job| !visible.contains(job));
+
+    let panes = &mut state.panes;
+    panes.split = split;
+    panes.jobs.insert(panes.focus + 1, job);
+    panes.reset_weights();
+
+    // The new pane doesn't have its area until the next draw
+    panes.areas.clear();
+
+    focus(state, state.panes.focus + 1);
+
+    Action::Tick
+}
+
+/// Close the focused pane, the job keeps running
+pub fn close(state: &mut App) -> Action {
+    let panes = &mut state.panes;
+
+    if !panes.is_split() {
+        return Action::Noop;
+    }
+
+    panes.jobs.remove(panes.focus);
+    panes.focus = panes.focus.saturating_sub(1);
+    panes.reset_weights();
+    panes.areas.clear();
+
+    state.current_job = panes.jobs[panes.focus];
+    state.mode = InputMode::Normal;
+
+    Action::Tick
+}
+
+/// Focus the pane next to the focused one
+pub fn focus_towards(state: &mut App, direction: Direction) -> Action {
+    let (cols, rows) = state.panes.grid();
+    let panes = state.panes.jobs.len();
+    let (col, row) = (state.panes.focus % cols, state.panes.focus / cols);
+
+    let target = match direction {
+        Direction::Left if col > 0 => state.panes.focus - 1,
+        Direction::Right if col + 1 < cols && state.panes.focus + 1 < panes => {
+            state.panes.focus + 1
+        }
+        Direction::Up if row > 0 => state.panes.focus - cols,
+        // Last row may have less panes
+        Direction::Down if row + 1 < rows => (state.panes.focus + cols).min(panes - 1),
+        _ => return Action::Noop,
+    };
+
+    focus(state, target);
+    Action::Tick
+}
+
+/// Focus the next pane, wrapping around
+pub fn focus_next(state: &mut App) -> Action {
+    if !state.panes.is_split() {
+        return Action::Noop;
+    }
+
+    focus(state, (state.panes.focus + 1) % state.panes.jobs.len());
+    Action::Tick
+}
+
+/// Grow or shrink the column or row of the focused pane. Right and down
+/// grow, left and up shrink.
+pub fn resize(state: &mut App, direction: Direction) -> Action {
+    let panes = &mut state.panes;
+    let (cols, _) = panes.grid();
+
+    let (weights, idx, grow) = match direction {
+        Direction::Left => (&mut panes.cols, panes.focus % cols, false),
+        Direction::Right => (&mut panes.cols, panes.focus % cols, true),
+        Direction::Up => (&mut panes.rows, panes.focus / cols, false),
+        Direction::Down => (&mut panes.rows, panes.focus / cols, true),
+    };
+
+    if weights.len() < 2 {
+        return Action::Noop;
+    }
+
+    let weight = &mut weights[idx];
+    *weight = if grow {
+        weight.saturating_add(1)
+    } else {
+        weight.saturating_sub(1).max(MIN_WEIGHT)
+    };
+
+    Action::Tick
+}
+
+/// Focus the pane under the mouse
+pub fn focus_at(state: &mut App, mouse: MouseEvent) {
+    if let Some(idx) = state.panes.pane_at(Position::new(mouse.column, mouse.row)) {
+        focus(state, idx);
+    }
+}
+
+/// Draw every pane on the job area
+pub fn render(state: &mut App, frame: &mut Frame, area: Rect) {
+    if !state.panes.is_split() {
+        state.panes.areas = vec![area];
+        let area = area.inner(Margin::both(1));
+        state.vterm_area = area;
+
+        if let Some(job) = state.current_job_mut() {
+            super::render_vterm(job, frame, area, true);
+        } else if state.anim.render_blink {
+            super::render_welcome_screen(state, area, frame.buffer_mut());
+        }
+
+        return;
+    }
+
+    // Keep the borders off the job block ones
+    let areas = state.panes.layout(area.inner(Margin::horizontal(1)));
+    let shown: Vec<Option<usize>> = state
+        .panes
+        .jobs
+        .iter()
+        .enumerate()
+        .map(|(idx, &job)| {
+            if idx == state.panes.focus {
+                state.current_job
+            } else {
+                job
+            }
+        })
+        .collect();
+
+    for (idx, (&pane_area, &job)) in areas.iter().zip(&shown).enumerate() {
+        let focused = idx == state.panes.focus;
+
+        let mut block = Block::new()
+            .borders(Borders::all())
+            .border_set(border::ROUNDED)
+            .border_style(if focused {
+                state.theme.accent
+            } else {
+                state.theme.border
+            });
+
+        if let Some(job) = job.and_then(|job| state.jobs.get(job)) {
+            block = block.title(format!(" {} ", job.title));
+        }
+
+        frame.render_widget(block, pane_area);
+
+        let inner = pane_area.inner(Margin::both(1));
+
+        if focused {
+            state.vterm_area = inner;
+        }
+
+        let Some(job) = job else {
+            frame.render_widget(
+                Text::from("No job, <Tab> picks one")
+                    .style(state.theme.normal)
+                    .centered(),
+                inner.inner_y(i32::from(inner.height / 2)),
+            );
+            continue;
+        };
+
+        // A job shown twice is sized by the focused pane, or the first one
+        let owner = if shown[state.panes.focus] == Some(job) {
+            state.panes.focus
+        } else {
+            shown
+                .iter()
+                .position(|&other| other == Some(job))
+                .unwrap_or(idx)
+        };
+
+        if let Some(job) = state.jobs.get_mut(job) {
+            super::render_vterm(job, frame, inner, owner == idx);
+        }
+    }
+
+    state.panes.areas = areas;
+}
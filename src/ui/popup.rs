@@ -1,4 +1,8 @@
+mod details;
+mod discover;
 mod edit;
+mod export;
+mod job_form;
 mod new_job;
 mod rename;
 
@@ -9,7 +13,11 @@ use crate::app::App;
 use crate::ui::common::AnimatedIsland;
 use crate::ui::prelude::*;
 
+pub use details::DetailsPopup;
+pub use discover::DiscoverPopup;
 pub use edit::EditPopup;
+pub use export::ExportPopup;
+pub use job_form::JobForm;
 pub use new_job::NewJobPopup;
 pub use rename::RenamePopup;
 
@@ -0,0 +1,133 @@
+use crate::app::App;
+use crate::job::{Job, env, shell, signal_name};
+use crate::ui::prelude::*;
+
+#[derive(Default)]
+pub struct DetailsPopup;
+
+impl DetailsPopup {
+    const WIDTH: u16 = 60;
+
+    fn lines(job: &Job) -> Vec<(&'static str, String)> {
+        let cwd = env::resolve_cwd(job.cwd.as_ref())
+            .map(|cwd| cwd.display().to_string())
+            .unwrap_or_default();
+
+        let mut lines = vec![
+            ("Id", job.id.to_string()),
+            ("Title", job.title.clone()),
+            ("Command", job.cmd.clone()),
+            ("Run", shell::describe(job.shell.as_ref())),
+            ("Cwd", cwd),
+            (
+                "Env",
+                if job.clean_env {
+                    "clean".to_owned()
+                } else {
+                    "inherited".to_owned()
+                },
+            ),
+        ];
+
+        lines.extend(
+            job.env
+                .iter()
+                .map(|(key, value)| ("", format!("{key}={value}"))),
+        );
+
+        lines.extend([
+            ("Status", job.status().label()),
+            (
+                "Pid",
+                job.running
+                    .as_ref()
+                    .map(|running| running.pid.to_string())
+                    .unwrap_or_default(),
+            ),
+            ("Restart", job.restart.policy.to_string()),
+            (
+                "Stop",
+                format!(
+                    "{}, SIGKILL after {}",
+                    signal_name(job.stop_signal.as_raw()).unwrap_or("?"),
+                    common::format_duration(job.stop_timeout),
+                ),
+            ),
+            ("Scrollback", format!("{} lines", job.scrollback)),
+            (
+                "Ready when",
+                job.ready_check
+                    .as_ref()
+                    .map_or_else(|| "spawned".to_owned(), ToString::to_string),
+            ),
+            (
+                "Depends on",
+                if job.depends_on.is_empty() {
+                    "nothing".to_owned()
+                } else {
+                    job.depends_on.join(", ")
+                },
+            ),
+            (
+                "Tags",
+                if job.tags.is_empty() {
+                    "none".to_owned()
+                } else {
+                    job.tags.join(", ")
+                },
+            ),
+        ]);
+
+        lines
+    }
+}
+
+impl Component for DetailsPopup {
+    type State = App;
+
+    fn draw(state: &mut Self::State, frame: &mut Frame, area: Rect) {
+        let Some(job) = state.current_job() else {
+            return;
+        };
+
+        let area = area.inner(Margin::new(1, 0));
+
+        let lines = Self::lines(job)
+            .into_iter()
+            .map(|(key, value)| {
+                Line::from(vec![
+                    Span::styled(format!("{key:>11} "), state.theme.accent),
+                    Span::styled(value, state.theme.normal),
+                ])
+            })
+            .collect::<Vec<_>>();
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(area, frame.buffer_mut());
+    }
+}
+
+impl popup::Popup for DetailsPopup {
+    const DURATION: usize = 7;
+
+    fn build<'a: 'app, 'app>(
+        island: popup::PopupBuilder<'a>,
+        app: &'app mut App,
+        area: Rect,
+    ) -> popup::PopupBuilder<'a> {
+        let height = app
+            .current_job()
+            .map_or(0, |job| Self::lines(job).len() as u16);
+
+        island
+            .direction(Side::Left)
+            .reserve(
+                area.reduce((0, 4))
+                    .set_width(Self::WIDTH)
+                    .offset(Offset::x(10))
+                    .centered((Self::WIDTH, height)),
+            )
+            .border_style(app.theme.border)
+    }
+}
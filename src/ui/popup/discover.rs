@@ -0,0 +1,215 @@
+use crate::app::{App, Notice};
+use crate::discover::{self, Discovered};
+use crate::ui::prelude::*;
+
+/// Tasks found in the manifests of the current directory, the selected ones
+/// are added as jobs
+#[derive(Default)]
+pub struct DiscoverPopup {
+    discovered: Discovered,
+    selected: Vec<bool>,
+    cursor: usize,
+    scroll: usize,
+}
+
+impl DiscoverPopup {
+    const WIDTH: u16 = 80;
+    const MAX_HEIGHT: u16 = 24;
+    /// Title, gap and buttons, plus the borders
+    const CHROME: u16 = 5;
+
+    fn toggle(&mut self, idx: usize) {
+        if let Some(selected) = self.selected.get_mut(idx) {
+            *selected ^= true;
+        }
+    }
+}
+
+impl Component for DiscoverPopup {
+    type State = App;
+
+    fn on_mount(state: &mut Self::State) {
+        let popup = &mut state.popup_discover;
+
+        popup.discovered = std::env::current_dir()
+            .map(|dir| discover::scan(&dir))
+            .unwrap_or_default();
+        popup.selected = vec![false; popup.discovered.tasks.len()];
+        popup.cursor = 0;
+        popup.scroll = 0;
+    }
+
+    async fn handle_key_events(state: &mut Self::State, key: KeyEvent) -> Action {
+        let popup = &mut state.popup_discover;
+        let last = popup.discovered.tasks.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Action::Quit,
+            KeyCode::Up | KeyCode::Char('k') => popup.cursor = popup.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => popup.cursor = (popup.cursor + 1).min(last),
+            KeyCode::Home => popup.cursor = 0,
+            KeyCode::End => popup.cursor = last,
+            KeyCode::Char(' ') => popup.toggle(popup.cursor),
+            KeyCode::Char('a') => {
+                let all = popup.selected.iter().all(|&selected| selected);
+                popup.selected.fill(!all);
+            }
+            KeyCode::Enter => {
+                // Nothing selected takes the task under the cursor
+                if !popup.selected.contains(&true) {
+                    popup.toggle(popup.cursor);
+                }
+
+                let jobs: Vec<_> = popup
+                    .discovered
+                    .tasks
+                    .iter()
+                    .zip(&popup.selected)
+                    .filter(|(_, selected)| **selected)
+                    .map(|(task, _)| task.to_job(state.default_shell.as_ref()))
+                    .collect();
+
+                let added = jobs.len();
+                let mut error = None;
+
+                for mut job in jobs {
+                    if let Err(err) = job.start() {
+                        error.get_or_insert(err.to_string());
+                    }
+
+                    state.push_job(job);
+                }
+
+                state.notice = Some(match error {
+                    Some(error) => Notice::error(error),
+                    None => Notice::info(format!(
+                        "Added {added} job{}",
+                        if added == 1 { "" } else { "s" }
+                    )),
+                });
+
+                return Action::Quit;
+            }
+            _ => return Action::Noop,
+        }
+
+        Action::Tick
+    }
+
+    fn draw(state: &mut Self::State, frame: &mut Frame, area: Rect) {
+        let area = area.inner(Margin::new(1, 0));
+        let popup = &mut state.popup_discover;
+        let errors = popup.discovered.errors.len() as u16;
+
+        let [title, list, errors_area, _, buttons] = Layout::vertical([
+            Constraint::Length(1), // Title
+            Constraint::Percentage(100),
+            Constraint::Length(errors),
+            Constraint::Length(1),
+            Constraint::Length(1), // Buttons
+        ])
+        .split(area);
+
+        Line::from(vec![
+            "Discover ".to_span().style(state.theme.normal),
+            "<Space>".to_span().style(state.theme.keybind_accent),
+            " select ".to_span().style(state.theme.normal),
+            "a".to_span().style(state.theme.keybind_accent),
+            "ll".to_span().style(state.theme.normal),
+        ])
+        .render(title, frame.buffer_mut());
+
+        if popup.discovered.tasks.is_empty() {
+            Text::from("No task found in this directory")
+                .style(state.theme.normal)
+                .centered()
+                .render(list.inner_y(i32::from(list.height / 2)), frame.buffer_mut());
+        }
+
+        // Keep the cursor in view
+        let height = usize::from(list.height).max(1);
+        popup.scroll = popup
+            .scroll
+            .clamp(popup.cursor.saturating_sub(height - 1), popup.cursor);
+
+        let name_width = popup
+            .discovered
+            .tasks
+            .iter()
+            .map(|task| task.name.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(24);
+
+        let lines: Vec<Line> = popup
+            .discovered
+            .tasks
+            .iter()
+            .zip(&popup.selected)
+            .enumerate()
+            .skip(popup.scroll)
+            .take(height)
+            .map(|(idx, (task, &selected))| {
+                let style = if idx == popup.cursor {
+                    state.theme.job_selected
+                } else {
+                    state.theme.normal
+                };
+
+                let name: String = task.name.chars().take(name_width).collect();
+
+                Line::from(vec![
+                    Span::styled(if selected { "[x] " } else { "[ ] " }, style),
+                    Span::styled(format!("{:<7}", task.source.label()), state.theme.accent),
+                    Span::styled(format!("{name:<name_width$}  "), style),
+                    Span::styled(task.cmd.clone(), state.theme.normal.dim()),
+                ])
+            })
+            .collect();
+
+        Text::from(lines).render(list, frame.buffer_mut());
+
+        Text::from_iter(
+            popup
+                .discovered
+                .errors
+                .iter()
+                .map(|err| Line::raw(err.to_string())),
+        )
+        .fg(Color::LightRed)
+        .render(errors_area, frame.buffer_mut());
+
+        popup::action_buttons(
+            [("ESC", Color::LightRed), ("Enter", Color::Blue)],
+            buttons,
+            frame.buffer_mut(),
+        );
+    }
+}
+
+impl popup::Popup for DiscoverPopup {
+    const DURATION: usize = 7;
+    const AUTO_CLOSE_EVENT: bool = false;
+
+    fn build<'a: 'app, 'app>(
+        island: popup::PopupBuilder<'a>,
+        app: &'app mut App,
+        area: Rect,
+    ) -> popup::PopupBuilder<'a> {
+        let discovered = &app.popup_discover.discovered;
+        let rows = discovered.tasks.len().max(1) + discovered.errors.len();
+        let height = (rows as u16 + Self::CHROME)
+            .min(Self::MAX_HEIGHT)
+            .min(area.height.saturating_sub(4));
+
+        island
+            .direction(Side::Left)
+            .reserve(
+                area.reduce((0, 4))
+                    .set_width(Self::WIDTH)
+                    .offset(Offset::x(10))
+                    .centered((Self::WIDTH, height)),
+            )
+            .border_style(app.theme.border)
+    }
+}
@@ -1,10 +1,10 @@
 use crate::app::App;
-use crate::job::{Job, JobStartError};
+use crate::job::JobStartError;
 use crate::ui::prelude::*;
 
 #[derive(Default)]
 pub struct EditPopup {
-    input: common::InputState,
+    form: popup::JobForm,
     last_err: Option<JobStartError>,
 }
 
@@ -12,10 +12,12 @@ impl Component for EditPopup {
     type State = App;
 
     fn on_mount(state: &mut Self::State) {
-        if let Some(job) = state.current_job() {
-            state.popup_edit.input.change_all(job.cmd.clone());
+        state.popup_edit.last_err = None;
+
+        if let Some(job) = state.current_job.and_then(|idx| state.jobs.get(idx)) {
+            state.popup_edit.form.load(job);
         } else {
-            state.popup_edit.input.clear();
+            state.popup_edit.form.clear(state.default_shell.as_ref());
         }
     }
 
@@ -28,39 +30,56 @@ impl Component for EditPopup {
                 code: KeyCode::Enter,
                 ..
             } => {
-                let content = state.popup_edit.input.content.clone();
+                if state.popup_edit.form.cmd.content.is_empty() {
+                    return Action::Noop;
+                }
 
-                if content.is_empty() {
+                let Some(job) = state.current_job.and_then(|idx| state.jobs.get_mut(idx)) else {
                     return Action::Noop;
+                };
+
+                // Checked on a copy so a bad form leaves the job untouched
+                let mut edited = job.clone_definition();
+
+                let valid = state
+                    .popup_edit
+                    .form
+                    .apply(&mut edited)
+                    .and_then(|()| edited.validate());
+
+                if let Err(err) = valid {
+                    state.popup_edit.last_err = Some(err);
+                    return Action::Tick;
                 }
 
-                let mut new_job = Job::new(&content);
+                // Untitled jobs are named after their command
+                let follow_title = job.title == job.cmd;
 
-                if let Err(err) = new_job.start() {
+                if let Err(err) = state.popup_edit.form.apply(job) {
                     state.popup_edit.last_err = Some(err);
                     return Action::Tick;
                 }
 
-                let Some(job) = state.current_job_mut() else {
-                    new_job.kill();
-                    return Action::Noop;
-                };
+                if follow_title {
+                    job.title.clone_from(&job.cmd);
+                }
 
-                *job = new_job;
+                // The new definition starts once the old process exited
+                job.restart_alive();
 
                 Action::Quit
             }
-            _ if state.popup_edit.input.handle_key(key) => Action::Tick,
+            _ if state.popup_edit.form.handle_key(key) => Action::Tick,
             _ => Action::Noop,
         }
     }
 
     fn draw(state: &mut Self::State, frame: &mut Frame, area: Rect) {
         let area = area.inner(Margin::new(1, 0));
-        let [title, input, error, _, buttons] = Layout::vertical([
-            Constraint::Length(1), // Title
-            Constraint::Length(3), // Input
-            Constraint::Length(2), // Error
+        let [title, form, error, _, buttons] = Layout::vertical([
+            Constraint::Length(1),                      // Title
+            Constraint::Length(popup::JobForm::HEIGHT), // Form
+            Constraint::Length(2),                      // Error
             Constraint::Length(1),
             Constraint::Length(1), // Buttons
         ])
@@ -68,11 +87,7 @@ impl Component for EditPopup {
 
         frame.draw(Text::raw("Edit").style(state.theme.normal), title, ());
 
-        frame.draw(
-            common::Input::default().border_style(state.theme.border.dim()),
-            input,
-            &mut state.popup_edit.input,
-        );
+        state.popup_edit.form.draw(&state.theme, frame, form);
 
         if let Some(ref err) = state.popup_edit.last_err {
             let err_msg = err.to_text();
@@ -104,9 +119,9 @@ impl popup::Popup for EditPopup {
             .direction(Side::Left)
             .reserve(
                 area.reduce((0, 20))
-                    .set_width(35)
+                    .set_width(45)
                     .offset(Offset::x(10))
-                    .centered((35, 8)),
+                    .centered((45, 16)),
             )
             .border_style(app.theme.border)
     }
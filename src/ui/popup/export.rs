@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+use crate::app::App;
+use crate::config::{self, export::DiffKind};
+use crate::ui::prelude::*;
+
+/// Confirms overwriting an existing project file, showing what changes
+#[derive(Default)]
+pub struct ExportPopup {
+    pub path: PathBuf,
+    /// Contents of the file on disk
+    pub old: String,
+    /// Contents about to be written
+    pub new: String,
+    /// Lines from `old` to `new`, computed once when opened
+    diff: Vec<(DiffKind, String)>,
+    scroll: usize,
+}
+
+impl ExportPopup {
+    const WIDTH: u16 = 70;
+    const MAX_HEIGHT: u16 = 30;
+}
+
+impl Component for ExportPopup {
+    type State = App;
+
+    fn on_mount(state: &mut Self::State) {
+        let popup = &mut state.popup_export;
+
+        popup.diff = config::export::diff(&popup.old, &popup.new)
+            .into_iter()
+            .map(|(kind, line)| (kind, line.to_owned()))
+            .collect();
+
+        // Start on the first change
+        popup.scroll = popup
+            .diff
+            .iter()
+            .position(|(kind, _)| *kind != DiffKind::Same)
+            .unwrap_or(0)
+            .saturating_sub(2);
+    }
+
+    async fn handle_key_events(state: &mut Self::State, key: KeyEvent) -> Action {
+        let popup = &mut state.popup_export;
+        let last = popup.diff.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('n' | 'q') => Action::Quit,
+            KeyCode::Enter | KeyCode::Char('y') => {
+                let path = std::mem::take(&mut popup.path);
+                let text = std::mem::take(&mut popup.new);
+                state.write_export(path, &text);
+                Action::Quit
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                popup.scroll = popup.scroll.saturating_sub(1);
+                Action::Tick
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.scroll = (popup.scroll + 1).min(last);
+                Action::Tick
+            }
+            KeyCode::PageUp => {
+                popup.scroll = popup.scroll.saturating_sub(10);
+                Action::Tick
+            }
+            KeyCode::PageDown => {
+                popup.scroll = (popup.scroll + 10).min(last);
+                Action::Tick
+            }
+            _ => Action::Noop,
+        }
+    }
+
+    fn draw(state: &mut Self::State, frame: &mut Frame, area: Rect) {
+        let area = area.inner(Margin::new(1, 0));
+        let [title, diff, _, buttons] = Layout::vertical([
+            Constraint::Length(1), // Title
+            Constraint::Percentage(100),
+            Constraint::Length(1),
+            Constraint::Length(1), // Buttons
+        ])
+        .split(area);
+
+        let popup = &state.popup_export;
+
+        frame.draw(
+            Text::raw(format!("Overwrite {}?", config::FILE_NAME)).style(state.theme.normal),
+            title,
+            (),
+        );
+
+        let lines: Vec<Line> = popup
+            .diff
+            .iter()
+            .skip(popup.scroll)
+            .map(|(kind, line)| match kind {
+                DiffKind::Same => Line::styled(format!("  {line}"), state.theme.normal.dim()),
+                DiffKind::Added => Line::styled(format!("+ {line}"), Color::Green),
+                DiffKind::Removed => Line::styled(format!("- {line}"), Color::LightRed),
+            })
+            .collect();
+
+        Text::from(lines).render(diff, frame.buffer_mut());
+
+        popup::action_buttons(
+            [("ESC", Color::LightRed), ("Enter", Color::Blue)],
+            buttons,
+            frame.buffer_mut(),
+        );
+    }
+}
+
+impl popup::Popup for ExportPopup {
+    const DURATION: usize = 7;
+    const AUTO_CLOSE_EVENT: bool = false;
+
+    fn build<'a: 'app, 'app>(
+        island: popup::PopupBuilder<'a>,
+        app: &'app mut App,
+        area: Rect,
+    ) -> popup::PopupBuilder<'a> {
+        let height = (app.popup_export.diff.len() as u16 + 4)
+            .min(Self::MAX_HEIGHT)
+            .min(area.height.saturating_sub(4));
+
+        island
+            .direction(Side::Left)
+            .reserve(
+                area.reduce((0, 4))
+                    .set_width(Self::WIDTH)
+                    .offset(Offset::x(10))
+                    .centered((Self::WIDTH, height)),
+            )
+            .border_style(app.theme.border)
+    }
+}
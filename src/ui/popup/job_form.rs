@@ -0,0 +1,210 @@
+//! Fields shared by the popups that create or edit a job.
+
+use std::path::PathBuf;
+
+use crate::job::shell::{self, Shell};
+use crate::job::{Job, JobStartError, env};
+use crate::theme::AppTheme;
+use crate::ui::prelude::*;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Field {
+    #[default]
+    Cmd,
+    Cwd,
+    Env,
+    CleanEnv,
+    Shell,
+}
+
+impl Field {
+    const ALL: [Self; 5] = [Self::Cmd, Self::Cwd, Self::Env, Self::CleanEnv, Self::Shell];
+
+    fn offset(self, offset: isize) -> Self {
+        let idx = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        let len = Self::ALL.len().casted::<isize>();
+
+        Self::ALL[(idx.casted::<isize>() + offset)
+            .rem_euclid(len)
+            .casted::<usize>()]
+    }
+}
+
+#[derive(Default)]
+pub struct JobForm {
+    pub cmd: common::InputState,
+    cwd: common::InputState,
+    env: common::InputState,
+    clean_env: bool,
+    shell: Option<Shell>,
+    /// Shell program kept while cycling through the modes
+    shell_program: Option<PathBuf>,
+    focus: Field,
+}
+
+impl JobForm {
+    /// Rows needed by [`JobForm::draw`]
+    pub const HEIGHT: u16 = 11;
+
+    pub fn clear(&mut self, shell: Option<&Shell>) {
+        self.cmd.clear();
+        self.cwd.clear();
+        self.env.clear();
+        self.clean_env = false;
+        self.set_shell(shell);
+        self.focus = Field::Cmd;
+    }
+
+    pub fn load(&mut self, job: &Job) {
+        self.cmd.change_all(job.cmd.clone());
+        self.cwd.change_all(
+            job.cwd
+                .as_ref()
+                .map(|cwd| cwd.display().to_string())
+                .unwrap_or_default(),
+        );
+        self.env.change_all(env::format_env(&job.env));
+        self.clean_env = job.clean_env;
+        self.set_shell(job.shell.as_ref());
+        self.focus = Field::Cmd;
+    }
+
+    fn set_shell(&mut self, shell: Option<&Shell>) {
+        self.shell = shell.cloned();
+        self.shell_program = shell.and_then(|shell| shell.program.clone());
+    }
+
+    /// Cycle through direct, shell and login shell
+    fn next_shell(&mut self) {
+        self.shell = match self.shell {
+            None => Some(Shell {
+                program: self.shell_program.clone(),
+                login: false,
+            }),
+            Some(ref shell) if !shell.login => Some(Shell {
+                login: true,
+                ..shell.clone()
+            }),
+            Some(_) => None,
+        };
+    }
+
+    /// Write the fields into `job`, validating them first
+    pub fn apply(&self, job: &mut Job) -> Result<(), JobStartError> {
+        let env = env::parse_env(&self.env.content)?;
+
+        job.with_cmd(self.cmd.content.clone());
+        job.cwd = Some(self.cwd.content.trim())
+            .filter(|cwd| !cwd.is_empty())
+            .map(PathBuf::from);
+        job.env = env;
+        job.clean_env = self.clean_env;
+        job.shell.clone_from(&self.shell);
+
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Tab | KeyCode::Down => {
+                self.focus = self.focus.offset(1);
+                true
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focus = self.focus.offset(-1);
+                true
+            }
+            KeyCode::Char(' ') if self.focus == Field::CleanEnv => {
+                self.clean_env ^= true;
+                true
+            }
+            KeyCode::Char(' ') if self.focus == Field::Shell => {
+                self.next_shell();
+                true
+            }
+            _ => match self.focus {
+                Field::Cmd => self.cmd.handle_key(key),
+                Field::Cwd => self.cwd.handle_key(key),
+                Field::Env => self.env.handle_key(key),
+                Field::CleanEnv | Field::Shell => false,
+            },
+        }
+    }
+
+    pub fn draw(&mut self, theme: &AppTheme, frame: &mut Frame, area: Rect) {
+        let [cmd, cwd, env, clean_env, run] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+        let fields = [
+            (Field::Cmd, "Command", cmd, &mut self.cmd),
+            (Field::Cwd, "Working directory", cwd, &mut self.cwd),
+            (Field::Env, "Env (KEY=VALUE ...)", env, &mut self.env),
+        ];
+
+        let mut focused = None;
+
+        for (field, label, area, input) in fields {
+            let label_style = if field == self.focus {
+                theme.accent
+            } else {
+                theme.normal.dim()
+            };
+
+            frame.draw_stateless(Text::raw(label).style(label_style), area);
+
+            if field == self.focus {
+                focused = Some((area, input));
+            } else {
+                frame.draw(
+                    common::Input::default().border_style(theme.border.dim()),
+                    area,
+                    input,
+                );
+            }
+        }
+
+        // Drawn last so it owns the cursor
+        if let Some((area, input)) = focused {
+            frame.draw(
+                common::Input::default().border_style(theme.border),
+                area,
+                input,
+            );
+        }
+
+        let focus_style = |field| {
+            if self.focus == field {
+                theme.accent
+            } else {
+                theme.normal
+            }
+        };
+
+        let check = if self.clean_env { "[x]" } else { "[ ]" };
+
+        frame.draw_stateless(
+            Line::from(vec![
+                check.to_span().style(focus_style(Field::CleanEnv)),
+                " Clean environment".to_span().style(theme.normal),
+            ]),
+            clean_env,
+        );
+
+        frame.draw_stateless(
+            Line::from(vec![
+                "Run ".to_span().style(theme.normal),
+                Span::styled(
+                    format!("<{}>", shell::describe(self.shell.as_ref())),
+                    focus_style(Field::Shell),
+                ),
+            ]),
+            run,
+        );
+    }
+}
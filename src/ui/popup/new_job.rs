@@ -4,7 +4,7 @@ use crate::ui::prelude::*;
 
 #[derive(Default)]
 pub struct NewJobPopup {
-    input: common::InputState,
+    form: popup::JobForm,
     last_err: Option<JobStartError>,
 }
 
@@ -12,7 +12,7 @@ impl Component for NewJobPopup {
     type State = App;
 
     fn on_mount(state: &mut Self::State) {
-        state.popup_new_job.input.clear();
+        state.popup_new_job.form.clear(state.default_shell.as_ref());
         state.popup_new_job.last_err = None;
     }
 
@@ -25,11 +25,17 @@ impl Component for NewJobPopup {
                 code: KeyCode::Enter,
                 ..
             } => {
-                let content = state.popup_new_job.input.content.clone();
+                let content = state.popup_new_job.form.cmd.content.clone();
 
                 let mut job = Job::new(&content);
 
-                if let Err(err) = job.start() {
+                let started = state
+                    .popup_new_job
+                    .form
+                    .apply(&mut job)
+                    .and_then(|()| job.start());
+
+                if let Err(err) = started {
                     state.popup_new_job.last_err = Some(err);
                     Action::Tick
                 } else {
@@ -38,17 +44,17 @@ impl Component for NewJobPopup {
                     Action::Quit
                 }
             }
-            _ if state.popup_new_job.input.handle_key(key) => Action::Tick,
+            _ if state.popup_new_job.form.handle_key(key) => Action::Tick,
             _ => Action::Noop,
         }
     }
 
     fn draw(state: &mut Self::State, frame: &mut Frame, area: Rect) {
         let area = area.inner(Margin::new(1, 0));
-        let [title, input, error, _, buttons] = Layout::vertical([
-            Constraint::Length(1), // Title
-            Constraint::Length(3), // Input
-            Constraint::Length(2), // Error
+        let [title, form, error, _, buttons] = Layout::vertical([
+            Constraint::Length(1),                      // Title
+            Constraint::Length(popup::JobForm::HEIGHT), // Form
+            Constraint::Length(2),                      // Error
             Constraint::Length(1),
             Constraint::Length(1), // Buttons
         ])
@@ -56,11 +62,7 @@ impl Component for NewJobPopup {
 
         frame.draw_stateless(Text::raw("New Job").style(state.theme.normal), title);
 
-        frame.draw(
-            common::Input::default().border_style(state.theme.border.dim()),
-            input,
-            &mut state.popup_new_job.input,
-        );
+        state.popup_new_job.form.draw(&state.theme, frame, form);
 
         if let Some(ref err) = state.popup_new_job.last_err {
             let err_msg = err.to_text();
@@ -90,7 +92,11 @@ impl popup::Popup for NewJobPopup {
     ) -> popup::PopupBuilder<'a> {
         island
             .direction(Side::Left)
-            .reserve(area.reduce_offset((10, 10)).set_width(35).centered((35, 8)))
+            .reserve(
+                area.reduce_offset((10, 10))
+                    .set_width(45)
+                    .centered((45, 16)),
+            )
             .border_style(app.theme.border)
     }
 }
@@ -1,9 +1,10 @@
 use crate::app::App;
+use crate::job::JobStatus;
 use crate::theme::AppTheme;
 use crate::ui::prelude::*;
 
 pub fn render(state: &mut App, area: Rect, frame: &mut Frame) {
-    let area = Layout::vertical([Constraint::Percentage(100), Constraint::Length(5)]).split(area);
+    let area = Layout::vertical([Constraint::Percentage(100), Constraint::Length(7)]).split(area);
 
     frame.draw(
         common::Blinker::new(render_help),
@@ -15,7 +16,8 @@ pub fn render(state: &mut App, area: Rect, frame: &mut Frame) {
 }
 
 fn render_sidebar(state: &App, frame: &mut Frame, area: Rect) {
-    let items = state.jobs.len() as u16;
+    // Jobs and the "All jobs" entry
+    let items = state.jobs.len() as u16 + 1;
 
     let area = area
         .inner_y(2)
@@ -34,23 +36,46 @@ fn render_sidebar(state: &App, frame: &mut Frame, area: Rect) {
 }
 
 fn render_sidebar_jobs(state: &App, frame: &mut Frame, area: Rect) {
+    render_all_jobs(state, frame, area.set_height(2));
+
     for (idx, item) in state.jobs.iter().enumerate() {
-        let area = area.inner_y(idx.casted::<i32>() * 2).set_height(1);
+        let area = area.inner_y(idx.casted::<i32>() * 2 + 2).set_height(1);
 
         {
-            let content = if let Some(status) = item.status() {
-                vec![
-                    "200ms ".to_span(),
-                    "●".to_span().fg(if status == 0 {
-                        Color::Green
-                    } else {
-                        Color::Red
-                    }),
-                ]
+            let status = item.status();
+            let runtime = common::format_duration(item.runtime().unwrap_or_default());
+
+            let label = if let Some(at) = item.next_retry {
+                let secs = at
+                    .saturating_duration_since(tokio::time::Instant::now())
+                    .as_secs_f32()
+                    .ceil();
+
+                format!("retry in {secs}s")
             } else {
-                vec!["●".to_span().fg(Color::Gray)]
+                match status {
+                    JobStatus::NotStarted if item.waiting_deps => "waiting deps".to_owned(),
+                    JobStatus::NotStarted => String::new(),
+                    JobStatus::Running if item.is_starting() => "starting".to_owned(),
+                    JobStatus::Running => runtime,
+                    JobStatus::Exited(_) | JobStatus::Signaled(..) => {
+                        format!("{} {runtime}", status.short_label())
+                    }
+                    JobStatus::Stopping | JobStatus::SpawnFailed(_) => status.short_label(),
+                }
             };
 
+            let dot_color = match status {
+                _ if item.next_retry.is_some() || item.is_starting() => Color::Yellow,
+                _ => status.color(),
+            };
+
+            let mut content = vec![Span::raw(label), " ●".to_span().fg(dot_color)];
+
+            if item.retries > 0 {
+                content.insert(0, Span::raw(format!("↻{} ", item.retries)));
+            }
+
             frame.draw(
                 common::Blinker::new(Line::from(content).right_aligned()),
                 area.inner_y(1).reduce((1, 0)).set_height(1),
@@ -58,7 +83,7 @@ fn render_sidebar_jobs(state: &App, frame: &mut Frame, area: Rect) {
             );
         }
 
-        let style = if state.current_job.is_some_and(|job| job == idx) {
+        let style = if !state.log.open && state.current_job.is_some_and(|job| job == idx) {
             state.theme.job_selected
         } else {
             state.theme.job_normal
@@ -71,12 +96,38 @@ fn render_sidebar_jobs(state: &App, frame: &mut Frame, area: Rect) {
     }
 }
 
+/// Entry of the combined log view
+fn render_all_jobs(state: &App, frame: &mut Frame, area: Rect) {
+    let running = state.jobs.iter().filter(|job| job.is_alive()).count();
+
+    frame.draw(
+        common::Blinker::new(Line::raw(format!("{running} running")).right_aligned()),
+        area.inner_y(1).reduce((1, 0)).set_height(1),
+        &state.anim,
+    );
+
+    let style = if state.log.open {
+        state.theme.job_selected
+    } else {
+        state.theme.job_normal
+    };
+
+    let bg = style.bg.unwrap_or(Color::Reset);
+
+    let area = common::pill(bg, area.set_height(1), frame.buffer_mut());
+    frame.draw_stateless("All jobs".to_text().style(style), area);
+}
+
 fn render_help(theme: &AppTheme, area: Rect, buf: &mut Buffer) {
     Text::from(vec![
         Line::from(vec![
             "c".to_span().style(theme.keybind_accent),
             "onfig".to_span().style(theme.normal),
         ]),
+        Line::from(vec![
+            "s".to_span().style(theme.keybind_accent),
+            "tart all".to_span().style(theme.normal),
+        ]),
         Line::from(vec![
             "stop ".to_span().style(theme.normal),
             "a".to_span().style(theme.keybind_accent),
@@ -84,7 +135,23 @@ fn render_help(theme: &AppTheme, area: Rect, buf: &mut Buffer) {
         ]),
         Line::from(vec![
             "n".to_span().style(theme.keybind_accent),
-            "ew process".to_span().style(theme.normal),
+            "ew process ".to_span().style(theme.normal),
+            "D".to_span().style(theme.keybind_accent),
+            "iscover".to_span().style(theme.normal),
+        ]),
+        Line::from(vec![
+            "E".to_span().style(theme.keybind_accent),
+            "xport to buj.toml".to_span().style(theme.normal),
+        ]),
+        Line::from(vec![
+            "|".to_span().style(theme.keybind_accent),
+            " ".to_span(),
+            "-".to_span().style(theme.keybind_accent),
+            " ".to_span(),
+            "#".to_span().style(theme.keybind_accent),
+            " split ".to_span().style(theme.normal),
+            "x".to_span().style(theme.keybind_accent),
+            " close pane".to_span().style(theme.normal),
         ]),
         Line::from(vec![
             "q".to_span().style(theme.keybind_accent),
@@ -9,16 +9,100 @@ use ratatui::{
 
 pub use vt100::{Cell, Screen};
 
+use crate::keys::InputModes;
+
+/// Immutable copy of the visible part of a [`Screen`].
+///
+/// Published by the job output reader after every batch, so rendering never
+/// waits on the parser.
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    rows: u16,
+    cols: u16,
+    cells: Vec<Cell>,
+    pub modes: InputModes,
+    /// Lines the view is scrolled back, `0` follows the output
+    pub scrollback: usize,
+    /// Line index of the first row, counted from the start of the output
+    pub top: usize,
+    /// Lines kept above the screen
+    pub history: usize,
+    /// Lines dropped from the scrollback, index of the oldest line kept
+    pub dropped: usize,
+    /// `(row, col)` of the terminal cursor
+    pub cursor: (u16, u16),
+    /// Search matches on the visible rows
+    pub highlights: Vec<Highlight>,
+    pub search: Option<SearchInfo>,
+}
+
+/// Search match on a row of a [`Snapshot`], `end` is exclusive
+#[derive(Clone, Copy)]
+pub struct Highlight {
+    pub row: u16,
+    pub start: u16,
+    pub end: u16,
+    /// Is the selected match
+    pub current: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct SearchInfo {
+    /// Index of the selected match
+    pub current: Option<usize>,
+    pub total: usize,
+}
+
+impl Snapshot {
+    pub fn new(screen: &Screen) -> Self {
+        let (rows, cols) = screen.size();
+
+        let cells = (0..rows)
+            .flat_map(|row| {
+                (0..cols).map(move |col| screen.cell(row, col).cloned().unwrap_or_default())
+            })
+            .collect();
+
+        Self {
+            rows,
+            cols,
+            cells,
+            modes: InputModes::from_screen(screen),
+            scrollback: screen.scrollback(),
+            top: 0,
+            history: 0,
+            dropped: 0,
+            cursor: screen.cursor_position(),
+            highlights: Vec::new(),
+            search: None,
+        }
+    }
+
+    /// `(rows, cols)`
+    pub const fn size(&self) -> (u16, u16) {
+        (self.rows, self.cols)
+    }
+
+    pub fn cell(&self, row: u16, col: u16) -> Option<&Cell> {
+        if row >= self.rows || col >= self.cols {
+            return None;
+        }
+
+        self.cells
+            .get(usize::from(row) * usize::from(self.cols) + usize::from(col))
+    }
+}
+
 /// A widget representing a pseudo-terminal screen.
 ///
 /// The `VTermWidget` displays the contents of a pseudo-terminal screen,
 /// which is typically populated with text and control sequences from a terminal emulator.
 /// It provides a visual representation of the terminal output within a TUI application.
 ///
-/// The contents of the pseudo-terminal screen are represented by a `vt100::Screen` object.
-/// The `vt100` library provides functionality for parsing and processing terminal control sequences
-/// and handling terminal state, allowing the `PseudoTerminal` widget to accurately render the
-/// terminal output.
+/// The contents of the pseudo-terminal screen are represented by a [`Snapshot`] of a
+/// `vt100::Screen` object. The `vt100` library provides functionality for parsing and processing
+/// terminal control sequences and handling terminal state, allowing the `PseudoTerminal` widget to
+/// accurately render the terminal output.
 ///
 /// # Examples
 ///
@@ -31,7 +115,7 @@ pub use vt100::{Cell, Screen};
 /// use vt100::Parser;
 ///
 /// let mut parser = vt100::Parser::new(24, 80, 0);
-/// let pseudo_term = PseudoTerminal::new(parser.screen())
+/// let pseudo_term = PseudoTerminal::new(&Snapshot::new(parser.screen()))
 ///     .style(
 ///         Style::default()
 ///             .fg(Color::White)
@@ -40,7 +124,7 @@ pub use vt100::{Cell, Screen};
 ///     );
 /// ```
 pub struct VTermWidget<'a> {
-    screen: &'a Screen,
+    screen: &'a Snapshot,
 }
 
 impl<'a> VTermWidget<'a> {
@@ -48,7 +132,7 @@ impl<'a> VTermWidget<'a> {
     ///
     /// # Arguments
     ///
-    /// * `screen`: The reference to the `Screen`.
+    /// * `screen`: The reference to the `Snapshot`.
     ///
     /// # Example
     ///
@@ -57,17 +141,17 @@ impl<'a> VTermWidget<'a> {
     /// use vt100::Parser;
     ///
     /// let mut parser = vt100::Parser::new(24, 80, 0);
-    /// let pseudo_term = PseudoTerminal::new(parser.screen());
+    /// let pseudo_term = PseudoTerminal::new(&Snapshot::new(parser.screen()));
     /// ```
     #[inline]
     #[must_use]
-    pub fn new(screen: &'a Screen) -> Self {
+    pub fn new(screen: &'a Snapshot) -> Self {
         VTermWidget { screen }
     }
 
     #[inline]
     #[must_use]
-    pub const fn screen(&self) -> &Screen {
+    pub const fn screen(&self) -> &Snapshot {
         self.screen
     }
 }
@@ -101,6 +185,24 @@ impl Widget for VTermWidget<'_> {
                 }
             }
         }
+
+        for highlight in &screen.highlights {
+            let bg = if highlight.current {
+                Color::LightRed
+            } else {
+                Color::Yellow
+            };
+
+            for col in highlight.start..highlight.end.min(cols) {
+                if highlight.row >= rows {
+                    break;
+                }
+
+                buf[(col + col_start, highlight.row + row_start)]
+                    .set_fg(Color::Black)
+                    .set_bg(bg);
+            }
+        }
     }
 }
 